@@ -188,10 +188,33 @@ fn main() {
         commands::list_sessions(no_formatting, short, reverse, active);
     } else if let Some(Command::Sessions(Sessions::PrettyPrintSession {
         session_name,
-        no_formatting,
+        display,
+        raw_layout,
+        export,
+        tab,
     })) = &opts.command
     {
-        fred::sessions::print_session_by_name(session_name, *no_formatting);
+        if let Some(out_path) = export {
+            match fred::sessions::export_session(session_name, out_path, tab.as_deref()) {
+                Ok(()) => {
+                    println!(
+                        "Exported session {} to {}",
+                        session_name,
+                        out_path.display()
+                    );
+                },
+                Err(e) => {
+                    eprintln!("Failed to export session: {}", e);
+                    std::process::exit(1)
+                },
+            }
+        } else if *raw_layout {
+            fred::sessions::print_raw_layout(session_name, display.json);
+        } else {
+            fred::sessions::print_session_by_name(session_name, display);
+        }
+    } else if let Some(Command::Sessions(Sessions::ListSessionsLong(options))) = &opts.command {
+        fred::sessions::list_sessions_long(options);
     } else if let Some(Command::Sessions(Sessions::ListAliases)) = opts.command {
         commands::list_aliases(opts);
     } else if let Some(Command::Sessions(Sessions::KillAllSessions { yes })) = opts.command {
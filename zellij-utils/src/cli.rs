@@ -2,38 +2,52 @@ use crate::data::{Direction, InputMode, Resize};
 use crate::setup::Setup;
 use crate::{
     consts::{ZELLIJ_CONFIG_DIR_ENV, ZELLIJ_CONFIG_FILE_ENV},
+    fred::template::parse_template,
     input::{layout::PluginUserConfiguration, options::CliOptions},
 };
-use clap::{Args, Parser, Subcommand};
+use clap::{ArgEnum, Args, Parser, Subcommand};
 use serde::{Deserialize, Serialize};
 use std::net::IpAddr;
 use std::path::PathBuf;
+use std::time::SystemTime;
 use url::Url;
 
 fn validate_session(name: &str) -> Result<String, String> {
-    #[cfg(unix)]
-    {
-        use crate::consts::ZELLIJ_SOCK_MAX_LENGTH;
-
-        let mut socket_path = crate::consts::ZELLIJ_SOCK_DIR.clone();
-        socket_path.push(name);
-
-        if socket_path.as_os_str().len() >= ZELLIJ_SOCK_MAX_LENGTH {
-            // socket path must be less than 108 bytes
-            let available_length = ZELLIJ_SOCK_MAX_LENGTH
-                .saturating_sub(socket_path.as_os_str().len())
-                .saturating_sub(1);
-
-            return Err(format!(
-                "session name must be less than {} characters",
-                available_length
-            ));
-        };
-    };
-
+    #[cfg(not(target_family = "wasm"))]
+    return crate::sessions::validate_session_name(name).map(|()| name.to_owned());
+    #[cfg(target_family = "wasm")]
     Ok(name.to_owned())
 }
 
+fn validate_template(template: &str) -> Result<String, String> {
+    parse_template(template).map(|_| template.to_owned())
+}
+
+fn validate_time_format(format: &str) -> Result<String, String> {
+    use chrono::format::{Item, StrftimeItems};
+
+    if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+        Err(format!("invalid time format {:?}", format))
+    } else {
+        Ok(format.to_owned())
+    }
+}
+
+fn parse_date(date: &str) -> Result<SystemTime, String> {
+    // A bare date means the start of that day
+    let date_time = if date.len() == "YYYY-MM-DD".len() {
+        format!("{} 00:00:00", date)
+    } else {
+        date.to_owned()
+    };
+    humantime::parse_rfc3339_weak(&date_time).map_err(|e| {
+        format!(
+            "invalid date \"{}\" ({}), expected YYYY-MM-DD or YYYY-MM-DD HH:MM:SS",
+            date, e
+        )
+    })
+}
+
 #[derive(Parser, Default, Debug, Clone, Serialize, Deserialize)]
 #[clap(version, name = "zellij")]
 pub struct CliArgs {
@@ -187,6 +201,620 @@ impl WebCli {
     }
 }
 
+#[derive(Debug, Clone, Default, Args, Serialize, Deserialize)]
+pub struct SessionDisplayOptions {
+    /// Print the output as JSON
+    #[clap(long, value_parser)]
+    pub json: bool,
+
+    /// Do not add colors and formatting to the list (useful for parsing)
+    #[clap(short, long, value_parser)]
+    pub no_formatting: bool,
+
+    /// Number of spaces used for each level of indentation
+    #[clap(long, value_parser, default_value("2"))]
+    pub indent: usize,
+
+    /// Show the command that resurrects each session with its saved layout
+    #[clap(long, value_parser)]
+    pub resurrect_command: bool,
+
+    /// Show how many panes resurrecting each session restores
+    #[clap(long, value_parser)]
+    pub pane_count: bool,
+
+    /// Show more details of each session, like whether it has floating panes, and print the
+    /// state found by --probe
+    #[clap(long, value_parser)]
+    pub verbose: bool,
+
+    /// Show the path of the file holding the resurrection layout of each session
+    #[clap(long, value_parser)]
+    pub layout_path: bool,
+
+    /// Prefix each tab with its position in the session
+    #[clap(long, value_parser)]
+    pub tab_index: bool,
+
+    /// Show how deeply the splits of each tab are nested after its name
+    #[clap(long, value_parser)]
+    pub tab_depth: bool,
+
+    /// Add the split direction and size of each tiled pane to its command in the JSON output
+    #[clap(long, value_parser, requires = "json")]
+    pub geometry: bool,
+
+    /// Pretty-print the JSON output with --indent, keeping arrays of plain values (e.g. the
+    /// warnings) on one line
+    #[clap(long, value_parser, requires = "json")]
+    pub json_compact_arrays: bool,
+
+    /// List the panes that only start the default shell as "Shell", so that every pane of a tab
+    /// is shown
+    #[clap(long, value_parser)]
+    pub include_dead_panes: bool,
+
+    /// Replace the home directory in paths with `~` (useful for sharing the output)
+    #[clap(long, value_parser)]
+    pub redact_cwd: bool,
+
+    /// Show paths with forward slashes (e.g. `C:/Users/me` rather than `C:\Users\me`). JSON
+    /// output keeps the paths as they are.
+    #[clap(long, value_parser)]
+    pub posix_paths: bool,
+
+    /// Label used for tabs without a name (default is "<Unnamed Tab>")
+    #[clap(long, value_parser)]
+    pub unnamed_label: Option<String>,
+
+    /// With --no-formatting, append the working directory of each command after a tab
+    #[clap(long, value_parser)]
+    pub show_cwd: bool,
+
+    /// Only show this many of the largest time units of each session's age (e.g. 2 gives
+    /// "2days 3h")
+    #[clap(long, value_parser)]
+    pub precision: Option<usize>,
+
+    /// Show ages as a whole number of seconds (e.g. "7384") instead of "2h 3m 4s"
+    #[clap(long, value_parser, conflicts_with = "precision")]
+    pub raw_time: bool,
+
+    /// Language of the words of ages (e.g. "2 Tage 3h" with de)
+    #[clap(long, arg_enum, value_parser, default_value = "en")]
+    pub lang: DurationLang,
+
+    /// Abbreviate the units of ages to letters (e.g. "2d 3h" instead of "2days 3h")
+    #[clap(long, value_parser, conflicts_with = "raw-time")]
+    pub abbrev: bool,
+
+    /// Show when sessions were created as a date and time in the local time zone, instead of
+    /// how long ago
+    #[clap(long, value_parser, conflicts_with_all = &["precision", "raw-time"])]
+    pub absolute_time: bool,
+
+    /// With --absolute-time, show times in UTC rather than in the local time zone
+    #[clap(long, value_parser, requires = "absolute-time")]
+    pub utc: bool,
+
+    /// With --absolute-time, the strftime format of the times (e.g. "%d/%m %H:%M")
+    #[clap(
+        long,
+        value_parser = validate_time_format,
+        requires = "absolute-time",
+        default_value = "%Y-%m-%d %H:%M:%S %:z"
+    )]
+    pub time_format: String,
+
+    /// Sort the tabs of each session (they are in layout order by default)
+    #[clap(long, arg_enum, value_parser)]
+    pub sort_tabs: Option<TabSortKey>,
+
+    /// Sort the commands of each tab alphabetically instead of in pane order
+    #[clap(long, value_parser)]
+    pub sort_commands: bool,
+
+    /// List the floating commands of each tab after the tiled ones, pass `false` to show them
+    /// as separate "Tiled" and "Floating" lists instead (`tiled_commands` and
+    /// `floating_commands` in the JSON output rather than `commands`)
+    #[clap(long, value_parser, action = clap::ArgAction::Set, default_value("true"))]
+    pub merge_floating_into_tiles: bool,
+
+    /// Leave out the newline after the JSON output
+    #[clap(long, value_parser, requires = "json")]
+    pub no_trailing_newline: bool,
+
+    /// Show plugin locations in full, including their scheme (e.g. "file:" or "https:")
+    #[clap(long, value_parser)]
+    pub plugin_url_full: bool,
+
+    /// Color the program of each command and its flags (arguments starting with `-`)
+    #[clap(long, value_parser)]
+    pub highlight_commands: bool,
+
+    /// Show how many sessions have been loaded so far on stderr (only when it is a terminal and
+    /// the output isn't JSON)
+    #[clap(long, value_parser)]
+    pub progress: bool,
+
+    /// In the JSON output, key the tabs of each session by name instead of listing them in an
+    /// array (unnamed tabs are keyed by their index)
+    #[clap(long, value_parser, requires = "json")]
+    pub tabs_as_json_object: bool,
+
+    /// Shorten command arguments longer than this many characters, ending them with "…"
+    #[clap(long, value_parser)]
+    pub max_arg_len: Option<usize>,
+
+    /// Replace session names with "session-1", "session-2", ... in the sorted order of the
+    /// names, to share the output without leaking project names
+    #[clap(long, value_parser)]
+    pub anonymize: bool,
+
+    /// Drop working directories and command arguments, and keep only the file name of programs
+    /// and edited files
+    #[clap(long, value_parser)]
+    pub anonymize_commands: bool,
+
+    /// Remove escape sequences and other control characters from session names, tab names and
+    /// commands, so that an untrusted session can't mess with the terminal
+    #[clap(long, value_parser)]
+    pub strip_ansi_from_names: bool,
+
+    /// Show the last exit status of each command. Resurrection layouts don't record it yet, so
+    /// this currently fails with an error
+    #[clap(long, value_parser)]
+    pub exit_status: bool,
+
+    /// Keep the layout of the formatted output (indentation, markers, tree glyphs) but leave out
+    /// the colors
+    #[clap(long, value_parser)]
+    pub mono: bool,
+
+    /// Color of the names of active sessions, as an SGR parameter (e.g. "92;1" for bold bright
+    /// green)
+    #[clap(long, value_parser, default_value = "92;1")]
+    pub active_name_color: String,
+
+    /// Color of the names of inactive sessions, as an SGR parameter (e.g. "2" for dim)
+    #[clap(long, value_parser, default_value = "2")]
+    pub inactive_name_color: String,
+
+    /// Text shown after the name of the current session
+    #[clap(long, value_parser, default_value = "(current)")]
+    pub current_marker: String,
+
+    /// Text shown after the name of active sessions (none by default)
+    #[clap(long, value_parser)]
+    pub active_marker: Option<String>,
+
+    /// Only show these parts of each session, separated by commas (name, created, last-active,
+    /// markers, tabs). Everything is shown by default.
+    #[clap(
+        long,
+        arg_enum,
+        value_parser,
+        value_delimiter = ',',
+        visible_alias = "select-fields"
+    )]
+    pub show: Vec<SessionField>,
+
+    /// Leave out the tabs of the current session, whose header is still shown
+    #[clap(long, value_parser)]
+    pub collapse_current: bool,
+}
+
+#[derive(ArgEnum, Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionSortKey {
+    /// Creation time, newest first unless --oldest-first is given
+    Created,
+    /// Session name, alphabetically
+    Name,
+    /// Active sessions first, then by creation time like `created`
+    Active,
+    /// Working directory of the first tab or command that has one, with the sessions without
+    /// one last unless --missing-cwd-first is given
+    Cwd,
+}
+
+impl Default for SessionSortKey {
+    fn default() -> Self {
+        Self::Created
+    }
+}
+
+#[derive(ArgEnum, Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DurationLang {
+    En,
+    De,
+    Fr,
+    Es,
+}
+
+impl Default for DurationLang {
+    fn default() -> Self {
+        Self::En
+    }
+}
+
+#[derive(ArgEnum, Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TabSortKey {
+    /// Number of commands, the busiest tab first
+    Count,
+    /// Tab name, alphabetically, with unnamed tabs last
+    Name,
+}
+
+/// Parts of a session shown by `--show`
+#[derive(ArgEnum, Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SessionField {
+    /// The session name
+    Name,
+    /// How long ago the session was created
+    Created,
+    /// How long ago an active session was started
+    LastActive,
+    /// The current and active markers
+    Markers,
+    /// The tabs and their commands
+    Tabs,
+}
+
+/// Versions of the `--porcelain` output. A version never changes once released, new fields go
+/// into a new version.
+#[derive(ArgEnum, Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PorcelainVersion {
+    /// One line per session with the tab-separated fields name, age in seconds, active
+    /// (true/false), current (true/false), number of tabs and number of commands
+    V1,
+}
+
+#[derive(Debug, Clone, Default, Args, Serialize, Deserialize)]
+pub struct LongListOptions {
+    #[clap(flatten)]
+    pub display: SessionDisplayOptions,
+
+    /// List the sessions in reverse order. Deprecated, prefer --newest-first and --oldest-first
+    /// which don't depend on the default order
+    #[clap(short, long, value_parser)]
+    pub reverse: bool,
+
+    /// List the most recently created sessions (the ones with the smallest age) first. This is
+    /// the default
+    #[clap(long, value_parser)]
+    pub newest_first: bool,
+
+    /// List the sessions that were created the longest ago (the ones with the largest age) first
+    #[clap(long, value_parser, conflicts_with = "newest-first")]
+    pub oldest_first: bool,
+
+    /// What to sort the sessions by. Sessions that compare equal are sorted by name
+    #[clap(long, arg_enum, value_parser, default_value = "created")]
+    pub sort: SessionSortKey,
+
+    /// Sort the sessions by name in descending order (same as `--sort name --reverse`)
+    #[clap(long, value_parser, conflicts_with_all = &["sort", "reverse"])]
+    pub reverse_alpha: bool,
+
+    /// With `--sort cwd`, put the sessions without a working directory first instead of last
+    #[clap(long, value_parser)]
+    pub missing_cwd_first: bool,
+
+    /// Pad session names so that their creation times line up in a column. With
+    /// --no-formatting, the ages are shortened and right-aligned in columns of their own.
+    #[clap(long, value_parser)]
+    pub align: bool,
+
+    /// Ignore the default filter in `session-filter.kdl` of the config directory, which holds
+    /// `include` and `exclude` nodes with session name patterns (e.g. `exclude "scratch-*"`)
+    #[clap(long, value_parser)]
+    pub no_default_filter: bool,
+
+    /// Only list sessions whose name contains this value
+    #[clap(long, value_parser)]
+    pub name: Option<String>,
+
+    /// Only list sessions whose name contains any of the patterns in this file, one per line.
+    /// Lines starting with `#` are ignored.
+    #[clap(long, value_parser)]
+    pub filter_file: Option<PathBuf>,
+
+    /// Only list the session whose name is exactly the one given to --name
+    #[clap(long, value_parser, requires = "name")]
+    pub exact: bool,
+
+    /// Only list sessions running a plugin whose location or alias contains this value
+    #[clap(long, value_parser)]
+    pub plugin: Option<String>,
+
+    /// Only list sessions with a pane editing a file, or with a path, editing a file whose path
+    /// contains it
+    #[clap(long, value_parser, value_name = "PATH")]
+    pub has_editfile: Option<Option<String>>,
+
+    /// Only list sessions with a command, file, cwd or plugin whose displayed text contains this
+    /// value
+    #[clap(long, value_parser)]
+    pub command_grep: Option<String>,
+
+    /// Only match --command-grep against commands
+    #[clap(long, value_parser, requires = "command-grep")]
+    pub command_only: bool,
+
+    /// Only show the tabs whose name contains this text, and the sessions that have any
+    #[clap(long, value_parser)]
+    pub tab_filter: Option<String>,
+
+    /// Only show the commands of floating panes
+    #[clap(long, value_parser, conflicts_with = "only-tiled")]
+    pub only_floating: bool,
+
+    /// Only show the commands of tiled panes
+    #[clap(long, value_parser)]
+    pub only_tiled: bool,
+
+    /// With --only-floating or --only-tiled, keep the sessions that are left without commands
+    #[clap(long, value_parser)]
+    pub show_empty: bool,
+
+    /// Only list sessions whose names were chosen by the user rather than generated
+    #[clap(long, value_parser)]
+    pub named_only: bool,
+
+    /// Only list inactive sessions created before every active session (cleanup candidates)
+    #[clap(long, value_parser)]
+    pub older_than_active: bool,
+
+    /// Only list sessions created at or after this UTC date (YYYY-MM-DD or
+    /// "YYYY-MM-DD HH:MM:SS")
+    #[clap(long, value_parser = parse_date)]
+    pub after: Option<SystemTime>,
+
+    /// Only list sessions created before this UTC date (YYYY-MM-DD or "YYYY-MM-DD HH:MM:SS")
+    #[clap(long, value_parser = parse_date)]
+    pub before: Option<SystemTime>,
+
+    /// Only list this many of the newest sessions, warning when there are more
+    #[clap(long, value_parser, default_value = "500")]
+    pub max_sessions: usize,
+
+    /// Only list the last N sessions in the sort order, e.g. the oldest ones when sorted newest
+    /// first
+    #[clap(long, value_parser, value_name = "N")]
+    pub tail: Option<usize>,
+
+    /// Number the sessions from 1 in the sort order, as a prefix of their name and an `id`
+    /// field in JSON. The ids stay the same as long as the filters and sort order do.
+    #[clap(long, value_parser)]
+    pub ids: bool,
+
+    /// Only list the session with this id from --ids, given the same filters and sort order
+    #[clap(long, value_parser)]
+    pub id: Option<usize>,
+
+    /// List every session, even if there are more than --max-sessions
+    #[clap(long, value_parser)]
+    pub all: bool,
+
+    /// Only list sessions created after this file was last modified (all of them if it doesn't
+    /// exist)
+    #[clap(long, value_parser)]
+    pub newer_than_file: Option<PathBuf>,
+
+    /// Only print the session this command runs in
+    #[clap(long, value_parser)]
+    pub whoami: bool,
+
+    /// Only check whether this session exists, exiting with 0 if it is active, 2 if it is
+    /// inactive (resurrectable) and 1 if it doesn't exist
+    #[clap(long, value_parser, value_name = "NAME")]
+    pub probe: Option<String>,
+
+    /// Print the number of listed sessions before the list. Only for the formatted list, tree and
+    /// --oneline output, the machine-readable formats are left as they are.
+    #[clap(
+        long,
+        value_parser,
+        conflicts_with_all = &[
+            "json",
+            "flat-json",
+            "porcelain",
+            "template",
+            "json-lines-pretty",
+            "top-commands",
+            "histogram",
+        ]
+    )]
+    pub header: bool,
+
+    /// Print only the number of sessions that would be listed
+    #[clap(long, value_parser)]
+    pub count: bool,
+
+    /// Print how many of the listed sessions are active, inactive and current
+    #[clap(long, value_parser)]
+    pub count_by_status: bool,
+
+    /// With --count-by-status, count every session rather than only the ones left by the filters
+    #[clap(long, value_parser, requires = "count-by-status")]
+    pub count_all: bool,
+
+    /// List the active sessions whose resurrection layout was saved before they were started,
+    /// as resurrecting them would lose what changed since
+    #[clap(long, value_parser)]
+    pub diff_against_running: bool,
+
+    /// Only list sessions with problems (missing working directories, programs that can't be
+    /// found or a corrupt layout) along with what is wrong, and exit with an error if there are
+    /// any
+    #[clap(long, value_parser)]
+    pub only_errors: bool,
+
+    /// Only list sessions with plugins that would fail to load (missing plugin files, unknown
+    /// builtin plugins and aliases) along with the plugins, and exit with an error if there are
+    /// any. With --only-errors, plugins are checked too.
+    #[clap(long, value_parser)]
+    pub check_plugins: bool,
+
+    /// Fail with the problems of every session instead of listing the sessions if there are any:
+    /// sessions skipped for a corrupt or unreadable layout, missing working directories and
+    /// programs, and plugins that would fail to load. Filters don't apply to the checks.
+    #[clap(long, value_parser)]
+    pub strict: bool,
+
+    /// List the sessions by index and prompt for one to print in detail. The prompt needs the
+    /// terminal, so this conflicts with --output-file.
+    #[clap(short, long, value_parser, conflicts_with_all = &["json", "output-file"])]
+    pub interactive: bool,
+
+    /// Print how often each program is run across the listed sessions instead of the sessions
+    #[clap(long, value_parser)]
+    pub top_commands: bool,
+
+    /// Print a bar chart of the number of commands of each listed session, scaled to the width
+    /// of the terminal, instead of the sessions
+    #[clap(long, value_parser, conflicts_with = "json")]
+    pub histogram: bool,
+
+    /// Print a `zellij delete-session` command for each listed inactive session instead of the
+    /// sessions. Conflicts with --anonymize, whose pseudonyms would be deleted instead.
+    #[clap(long, value_parser, conflicts_with = "anonymize")]
+    pub print_kill_commands: bool,
+
+    /// Run this shell command for each listed session instead of listing them, with {name}
+    /// replaced by the session name (e.g. "zellij kill-session {name}"). The commands write to
+    /// the terminal, so this conflicts with --output-file.
+    #[clap(
+        long,
+        value_parser,
+        conflicts_with_all = &["json", "anonymize", "output-file"]
+    )]
+    pub exec: Option<String>,
+
+    /// Don't print the commands run by --exec to stderr
+    #[clap(long, value_parser, requires = "exec")]
+    pub quiet: bool,
+
+    /// Let --exec run commands that look destructive (e.g. ones that delete or kill)
+    #[clap(long, value_parser, requires = "exec")]
+    pub yes: bool,
+
+    /// Print each session with its tabs and commands on a single line
+    #[clap(long, value_parser, conflicts_with_all = &["json", "template"])]
+    pub oneline: bool,
+
+    /// Separator between tabs with --oneline
+    #[clap(long, value_parser, default_value = " | ", requires = "oneline")]
+    pub tab_sep: String,
+
+    /// Separator between the commands of a tab with --oneline
+    #[clap(long, value_parser, default_value = ", ", requires = "oneline")]
+    pub cmd_sep: String,
+
+    /// Print the sessions in a format that stays the same across releases, for scripts. v1 (the
+    /// default) prints a line per session with the tab-separated name, age in seconds, active,
+    /// current, number of tabs and number of commands
+    #[clap(
+        long,
+        arg_enum,
+        value_parser,
+        min_values = 0,
+        require_equals = true,
+        default_missing_value = "v1",
+        conflicts_with_all = &["json", "template", "oneline"]
+    )]
+    pub porcelain: Option<PorcelainVersion>,
+
+    /// Print a JSON array with one object per command, holding its session, tab and working
+    /// directory, instead of sessions with nested tabs
+    #[clap(
+        long,
+        value_parser,
+        conflicts_with_all = &["json", "template", "oneline", "json-lines-pretty"]
+    )]
+    pub flat_json: bool,
+
+    /// Print each session as a JSON object on its own line, starting with the name and status
+    #[clap(long, value_parser, conflicts_with_all = &["json", "template", "oneline"])]
+    pub json_lines_pretty: bool,
+
+    /// Group the sessions by the project directory their commands run in: the closest directory
+    /// with a git repository, or the first --cwd-root-depth directories
+    #[clap(
+        long,
+        value_parser,
+        conflicts_with_all = &["json", "template", "oneline", "json-lines-pretty"]
+    )]
+    pub group_by_cwd_root: bool,
+
+    /// Group the sessions by the built-in layout or layout of the layout directory they were
+    /// started from, with the others under "(ad-hoc)"
+    #[clap(
+        long,
+        value_parser,
+        conflicts_with_all = &["json", "template", "oneline", "json-lines-pretty", "group-by-cwd-root"]
+    )]
+    pub group_by_template: bool,
+
+    /// Number of leading directories of a working directory that make up its project with
+    /// --group-by-cwd-root (e.g. 3 turns /home/user/project/src into /home/user/project)
+    #[clap(long, value_parser, requires = "group-by-cwd-root")]
+    pub cwd_root_depth: Option<usize>,
+
+    /// Print the sessions, tabs and commands as a tree
+    #[clap(
+        long,
+        value_parser,
+        conflicts_with_all = &["json", "template", "oneline", "json-lines-pretty", "group-by-cwd-root", "group-by-template"]
+    )]
+    pub tree: bool,
+
+    /// Draw the --tree with ASCII characters instead of box-drawing ones (the default when the
+    /// locale isn't UTF-8)
+    #[clap(long, value_parser, requires = "tree")]
+    pub ascii: bool,
+
+    /// With --json, print an object with the sessions and a `warnings` array instead of
+    /// printing the warnings to stderr
+    #[clap(long, value_parser, requires = "json")]
+    pub json_warnings: bool,
+
+    /// With --json, print an object with the version of the JSON format and the sessions
+    /// instead of a bare array
+    #[clap(long, value_parser, requires = "json")]
+    pub envelope: bool,
+
+    /// Print each session on one line using a template with the placeholders
+    /// {name}, {age}, {tabs}, {commands} and {current} (e.g. "{name} {age}")
+    #[clap(long, value_parser = validate_template, conflicts_with = "json")]
+    pub template: Option<String>,
+
+    /// Read the sessions from a file written by --json instead of the session cache. Can be
+    /// given more than once to list the sessions of several files.
+    #[clap(long, value_parser)]
+    pub from_file: Vec<PathBuf>,
+
+    /// Read the sessions from another session_info folder instead of ours, e.g.
+    /// /home/alice/.cache/zellij/<VERSION>/session_info to audit the sessions of another user.
+    /// None of them is shown as running.
+    #[clap(long, value_parser, conflicts_with = "from-file")]
+    pub dir: Option<PathBuf>,
+
+    /// Combine the sessions with the same name (e.g. from dumps of before and after a crash)
+    /// into one, with the newest creation time and the tabs and commands of all of them
+    #[clap(long, value_parser)]
+    pub merge_duplicates: bool,
+
+    /// Print how long reading, parsing, sorting and printing the sessions took to stderr
+    #[clap(long, value_parser, hide = true)]
+    pub benchmark: bool,
+
+    /// Write the output to a file instead of stdout, creating its directory if needed. Colors
+    /// are left out, and a path ending in .json writes JSON unless another format is chosen.
+    #[clap(long, value_parser)]
+    pub output_file: Option<PathBuf>,
+}
+
 #[derive(Debug, Subcommand, Clone, Serialize, Deserialize)]
 pub enum SessionCommand {
     /// Change the behaviour of zellij
@@ -200,10 +828,21 @@ pub enum Sessions {
     PrettyPrintSession {
         /// The name of the session to print
         session_name: String,
-        /// Do not add colors and formatting to the list (useful for parsing)
-        #[clap(short, long)]
-        no_formatting: bool,
+        #[clap(flatten)]
+        display: SessionDisplayOptions,
+        /// Print the parsed resurrection layout of the session instead of its summary
+        #[clap(long, value_parser)]
+        raw_layout: bool,
+        /// Write the resurrection layout of the session to this path as a KDL layout file
+        #[clap(long, value_parser, conflicts_with = "raw-layout")]
+        export: Option<PathBuf>,
+        /// Only export this tab, given by name or by index
+        #[clap(long, value_parser, requires = "export")]
+        tab: Option<String>,
     },
+    /// List sessions along with the tabs and commands they would resurrect
+    #[clap(visible_alias = "ll")]
+    ListSessionsLong(Box<LongListOptions>),
     /// List active sessions
     #[clap(visible_alias = "ls")]
     ListSessions {
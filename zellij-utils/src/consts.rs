@@ -4,7 +4,10 @@ use crate::home::find_default_config_dir;
 use directories::ProjectDirs;
 use include_dir::{include_dir, Dir};
 use lazy_static::lazy_static;
-use std::{path::PathBuf, sync::OnceLock};
+use std::{
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
 use uuid::Uuid;
 
 pub const ZELLIJ_CONFIG_FILE_ENV: &str = "ZELLIJ_CONFIG_FILE";
@@ -25,7 +28,15 @@ pub fn session_info_cache_file_name(session_name: &str) -> PathBuf {
 }
 
 pub fn session_layout_cache_file_name(session_name: &str) -> PathBuf {
-    session_info_folder_for_session(session_name).join("session-layout.kdl")
+    session_layout_file_in(&ZELLIJ_SESSION_INFO_CACHE_DIR, session_name)
+}
+
+/// The resurrection layout of `session_name` in a session_info folder other than ours, e.g. the
+/// one of another user
+pub fn session_layout_file_in(session_info_dir: &Path, session_name: &str) -> PathBuf {
+    session_info_dir
+        .join(session_name)
+        .join("session-layout.kdl")
 }
 
 pub fn session_info_folder_for_session(session_name: &str) -> PathBuf {
@@ -0,0 +1,140 @@
+//! The default filter of session listings, read from `session-filter.kdl` in the config
+//! directory.
+//!
+//! The file holds `include` and `exclude` nodes with a session name pattern each, e.g.
+//!
+//! ```kdl
+//! exclude "scratch-*"
+//! include "work"
+//! ```
+//!
+//! A pattern matches names that contain it, or with a `*`, names that it matches as a whole, where
+//! `*` stands for any text. A session is listed if it matches any `include` pattern (or there are
+//! none) and no `exclude` pattern.
+
+use std::path::{Path, PathBuf};
+
+use kdl::KdlDocument;
+
+use crate::home::find_default_config_dir;
+
+pub const DEFAULT_FILTER_FILE_NAME: &str = "session-filter.kdl";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DefaultFilter {
+    include: Vec<String>,
+    exclude: Vec<String>,
+}
+
+impl DefaultFilter {
+    /// Reads the default filter from the config directory, if there is one
+    pub fn load() -> Result<Option<Self>, String> {
+        match default_filter_path() {
+            Some(path) if path.exists() => Self::from_path(&path).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    pub fn from_path(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        Self::parse(&contents).map_err(|e| format!("invalid {}: {}", path.display(), e))
+    }
+
+    pub fn parse(contents: &str) -> Result<Self, String> {
+        let document: KdlDocument = contents.parse().map_err(|e| format!("{}", e))?;
+        let mut filter = Self::default();
+        for node in document.nodes() {
+            let name = node.name().value();
+            let patterns = match name {
+                "include" => &mut filter.include,
+                "exclude" => &mut filter.exclude,
+                other => return Err(format!("unknown node {:?}", other)),
+            };
+            let pattern = node
+                .entries()
+                .first()
+                .and_then(|entry| entry.value().as_string())
+                .ok_or_else(|| format!("{} needs a name pattern", name))?;
+            patterns.push(pattern.to_owned());
+        }
+        Ok(filter)
+    }
+
+    pub fn matches(&self, session_name: &str) -> bool {
+        let is_included = self.include.is_empty()
+            || self
+                .include
+                .iter()
+                .any(|pattern| pattern_matches(pattern, session_name));
+        is_included
+            && !self
+                .exclude
+                .iter()
+                .any(|pattern| pattern_matches(pattern, session_name))
+    }
+}
+
+fn default_filter_path() -> Option<PathBuf> {
+    find_default_config_dir().map(|dir| dir.join(DEFAULT_FILTER_FILE_NAME))
+}
+
+/// Whether `name` contains `pattern`, or if the pattern has a `*`, whether it matches the whole
+/// name with `*` standing for any text
+fn pattern_matches(pattern: &str, name: &str) -> bool {
+    if !pattern.contains('*') {
+        return name.contains(pattern);
+    }
+    let mut parts = pattern.split('*');
+    // There is always a first and a last part, which may be empty
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = name.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = parts.collect();
+    let last = parts.pop().unwrap_or_default();
+    for part in parts {
+        match rest.find(part) {
+            Some(position) => rest = &rest[position + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patterns_match_substrings_or_wildcards() {
+        assert!(pattern_matches("work", "my-work-session"));
+        assert!(pattern_matches("scratch-*", "scratch-1"));
+        assert!(!pattern_matches("scratch-*", "my-scratch-1"));
+        assert!(pattern_matches("*-tmp", "build-tmp"));
+        assert!(pattern_matches("a*b*c", "a-x-b-y-c"));
+        assert!(!pattern_matches("a*b*c", "a-x-c"));
+        assert!(!pattern_matches("ab*ba", "aba"));
+        assert!(pattern_matches("*", "anything"));
+    }
+
+    #[test]
+    fn filter_includes_then_excludes() {
+        let filter = DefaultFilter::parse(
+            r#"
+            include "work"
+            exclude "*-old"
+            "#,
+        )
+        .unwrap();
+        assert!(filter.matches("work-api"));
+        assert!(!filter.matches("work-api-old"));
+        assert!(!filter.matches("personal"));
+    }
+
+    #[test]
+    fn filter_rejects_unknown_nodes() {
+        assert!(DefaultFilter::parse(r#"includes "work""#).is_err());
+        assert!(DefaultFilter::parse("exclude").is_err());
+    }
+}
@@ -0,0 +1,122 @@
+//! Session ages in words, in English like `humantime` or in one of the `--lang` translations.
+
+use std::time::Duration;
+
+use humantime::format_duration;
+
+use crate::cli::DurationLang;
+
+/// The unit lengths used by humantime, from years down to seconds
+pub const UNIT_SECS: [u64; 6] = [31_557_600, 2_630_016, 86_400, 3_600, 60, 1];
+
+/// The singular and plural of each unit in `UNIT_SECS`, written right after the number. English
+/// is left to `humantime`.
+struct UnitWords([(&'static str, &'static str); 6]);
+
+const GERMAN: UnitWords = UnitWords([
+    (" Jahr", " Jahre"),
+    (" Monat", " Monate"),
+    (" Tag", " Tage"),
+    ("h", "h"),
+    ("m", "m"),
+    ("s", "s"),
+]);
+const FRENCH: UnitWords = UnitWords([
+    (" an", " ans"),
+    (" mois", " mois"),
+    (" jour", " jours"),
+    ("h", "h"),
+    ("min", "min"),
+    ("s", "s"),
+]);
+const SPANISH: UnitWords = UnitWords([
+    (" año", " años"),
+    (" mes", " meses"),
+    (" día", " días"),
+    ("h", "h"),
+    ("m", "m"),
+    ("s", "s"),
+]);
+const ABBREVIATED: UnitWords = UnitWords([
+    ("y", "y"),
+    ("mo", "mo"),
+    ("d", "d"),
+    ("h", "h"),
+    ("m", "m"),
+    ("s", "s"),
+]);
+
+/// Formats `age` like "2days 3h 4m", with the unit words of `lang` or abbreviated to single
+/// letters (e.g. "2d 3h 4m"). Parts of a second are left out, except in the English default that
+/// keeps the output of `humantime`.
+pub fn format_age_words(age: Duration, lang: DurationLang, abbrev: bool) -> String {
+    let words = match (lang, abbrev) {
+        (_, true) => &ABBREVIATED,
+        (DurationLang::En, false) => return format_duration(age).to_string(),
+        (DurationLang::De, false) => &GERMAN,
+        (DurationLang::Fr, false) => &FRENCH,
+        (DurationLang::Es, false) => &SPANISH,
+    };
+    let mut secs = age.as_secs();
+    let mut parts = Vec::new();
+    for (unit, (singular, plural)) in UNIT_SECS.iter().zip(words.0) {
+        let count = secs / unit;
+        secs %= unit;
+        if count > 0 {
+            let word = if count == 1 { singular } else { plural };
+            parts.push(format!("{}{}", count, word));
+        }
+    }
+    if parts.is_empty() {
+        format!("0{}", words.0[5].1)
+    } else {
+        parts.join(" ")
+    }
+}
+
+/// Phrases an age formatted by `format_age_words` as being in the past (e.g. "3h ago")
+pub fn format_ago(age: &str, lang: DurationLang) -> String {
+    match lang {
+        DurationLang::En => format!("{} ago", age),
+        DurationLang::De => format!("vor {}", age),
+        DurationLang::Fr => format!("il y a {}", age),
+        DurationLang::Es => format!("hace {}", age),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AGE: Duration = Duration::from_secs(2 * 86_400 + 3 * 3_600 + 1);
+
+    #[test]
+    fn english_matches_humantime() {
+        assert_eq!(
+            format_age_words(AGE, DurationLang::En, false),
+            "2days 3h 1s"
+        );
+        assert_eq!(
+            format_age_words(Duration::from_millis(1_500), DurationLang::En, false),
+            "1s 500ms"
+        );
+    }
+
+    #[test]
+    fn translations_and_abbreviations() {
+        assert_eq!(
+            format_age_words(AGE, DurationLang::De, false),
+            "2 Tage 3h 1s"
+        );
+        assert_eq!(
+            format_age_words(Duration::from_secs(86_400), DurationLang::Fr, false),
+            "1 jour"
+        );
+        assert_eq!(format_age_words(AGE, DurationLang::Es, true), "2d 3h 1s");
+        assert_eq!(
+            format_age_words(Duration::ZERO, DurationLang::De, true),
+            "0s"
+        );
+        assert_eq!(format_ago("2d", DurationLang::De), "vor 2d");
+    }
+}
@@ -0,0 +1,312 @@
+//! Pretty JSON for `--json-compact-arrays`.
+//!
+//! Objects and arrays of objects are spread over lines like `serde_json::to_string_pretty` does,
+//! but arrays of plain values (the arguments of a command, a list of warnings) stay on one line.
+
+use std::io::{self, Write};
+
+use serde::Serialize;
+use serde_json::ser::{CompactFormatter, Formatter, Serializer};
+
+/// Serializes `value` as pretty JSON, indented by `indent` spaces per level, with arrays of plain
+/// values kept on one line
+pub fn to_string_with_compact_arrays<T: Serialize + ?Sized>(value: &T, indent: usize) -> String {
+    // A first pass finds the arrays that only hold plain values, in the order they are written
+    let mut one_line_arrays = Vec::new();
+    let classifier = ArrayClassifier {
+        one_line_arrays: &mut one_line_arrays,
+        open_arrays: Vec::new(),
+        pending_element: false,
+    };
+    value
+        .serialize(&mut Serializer::with_formatter(io::sink(), classifier))
+        .expect("Should always serialize correctly");
+
+    let mut json = Vec::new();
+    let mut serializer = Serializer::with_formatter(
+        &mut json,
+        CompactArraysFormatter::new(indent, one_line_arrays),
+    );
+    value
+        .serialize(&mut serializer)
+        .expect("Should always serialize correctly");
+    String::from_utf8(json).expect("JSON is always valid UTF-8")
+}
+
+/// Records for every array, in the order they start, whether all of its elements are plain
+/// values. Empty arrays count as plain.
+struct ArrayClassifier<'a> {
+    one_line_arrays: &'a mut Vec<bool>,
+    /// The positions in `one_line_arrays` of the arrays being written, innermost last
+    open_arrays: Vec<usize>,
+    /// Set from the start of an array element until a container starts or the array ends
+    pending_element: bool,
+}
+
+impl ArrayClassifier<'_> {
+    /// Marks the array around a container element as not plain
+    fn start_container(&mut self) {
+        if std::mem::take(&mut self.pending_element) {
+            if let Some(&array) = self.open_arrays.last() {
+                self.one_line_arrays[array] = false;
+            }
+        }
+    }
+}
+
+impl Formatter for ArrayClassifier<'_> {
+    fn begin_array<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.start_container();
+        self.open_arrays.push(self.one_line_arrays.len());
+        self.one_line_arrays.push(true);
+        CompactFormatter.begin_array(writer)
+    }
+
+    fn end_array<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.open_arrays.pop();
+        // The last element was plain if it is still pending
+        self.pending_element = false;
+        CompactFormatter.end_array(writer)
+    }
+
+    fn begin_array_value<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        self.pending_element = true;
+        CompactFormatter.begin_array_value(writer, first)
+    }
+
+    fn begin_object<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.start_container();
+        CompactFormatter.begin_object(writer)
+    }
+}
+
+#[derive(Debug)]
+enum Container {
+    Object { is_empty: bool },
+    Array { one_line: bool },
+}
+
+/// Writes pretty JSON, except for the arrays that `ArrayClassifier` found to only hold plain
+/// values
+struct CompactArraysFormatter {
+    indent: usize,
+    /// Whether each array fits on one line, in the order they start
+    one_line_arrays: std::vec::IntoIter<bool>,
+    /// The objects and arrays being written, innermost last
+    containers: Vec<Container>,
+    /// Set between the start of an array element and its value, to whether it is the first one
+    pending_element: Option<bool>,
+}
+
+impl CompactArraysFormatter {
+    fn new(indent: usize, one_line_arrays: Vec<bool>) -> Self {
+        Self {
+            indent,
+            one_line_arrays: one_line_arrays.into_iter(),
+            containers: Vec::new(),
+            pending_element: None,
+        }
+    }
+
+    fn write_indent<W: ?Sized + Write>(&self, writer: &mut W) -> io::Result<()> {
+        let width = self.indent * self.containers.len();
+        write!(writer, "{:width$}", "", width = width)
+    }
+
+    /// Writes what goes before a value: nothing, except for array elements
+    fn start_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        let Some(first) = self.pending_element.take() else {
+            return Ok(());
+        };
+        match self.containers.last() {
+            Some(Container::Array { one_line: true }) => {
+                if !first {
+                    writer.write_all(b", ")?;
+                }
+                Ok(())
+            },
+            _ => {
+                writer.write_all(if first { b"\n" } else { b",\n" })?;
+                self.write_indent(writer)
+            },
+        }
+    }
+}
+
+macro_rules! plain_values {
+    ($($method:ident($value:ty)),* $(,)?) => {
+        $(
+            fn $method<W: ?Sized + Write>(
+                &mut self,
+                writer: &mut W,
+                value: $value,
+            ) -> io::Result<()> {
+                self.start_value(writer)?;
+                CompactFormatter.$method(writer, value)
+            }
+        )*
+    };
+}
+
+impl Formatter for CompactArraysFormatter {
+    plain_values!(
+        write_bool(bool),
+        write_i8(i8),
+        write_i16(i16),
+        write_i32(i32),
+        write_i64(i64),
+        write_i128(i128),
+        write_u8(u8),
+        write_u16(u16),
+        write_u32(u32),
+        write_u64(u64),
+        write_u128(u128),
+        write_f32(f32),
+        write_f64(f64),
+        write_number_str(&str),
+        write_raw_fragment(&str),
+    );
+
+    fn write_null<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.start_value(writer)?;
+        writer.write_all(b"null")
+    }
+
+    fn begin_string<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.start_value(writer)?;
+        writer.write_all(b"\"")
+    }
+
+    fn begin_array<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.start_value(writer)?;
+        // The classifier saw the same arrays in the same order
+        let one_line = self.one_line_arrays.next().unwrap_or(false);
+        self.containers.push(Container::Array { one_line });
+        writer.write_all(b"[")
+    }
+
+    fn end_array<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        if let Some(Container::Array { one_line: false }) = self.containers.pop() {
+            writer.write_all(b"\n")?;
+            self.write_indent(writer)?;
+        }
+        writer.write_all(b"]")
+    }
+
+    fn begin_array_value<W: ?Sized + Write>(
+        &mut self,
+        _writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        self.pending_element = Some(first);
+        Ok(())
+    }
+
+    fn end_array_value<W: ?Sized + Write>(&mut self, _writer: &mut W) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn begin_object<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        self.start_value(writer)?;
+        self.containers.push(Container::Object { is_empty: true });
+        writer.write_all(b"{")
+    }
+
+    fn end_object<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        if let Some(Container::Object { is_empty: false }) = self.containers.pop() {
+            writer.write_all(b"\n")?;
+            self.write_indent(writer)?;
+        }
+        writer.write_all(b"}")
+    }
+
+    fn begin_object_key<W: ?Sized + Write>(
+        &mut self,
+        writer: &mut W,
+        first: bool,
+    ) -> io::Result<()> {
+        if let Some(Container::Object { is_empty }) = self.containers.last_mut() {
+            *is_empty = false;
+        }
+        writer.write_all(if first { b"\n" } else { b",\n" })?;
+        self.write_indent(writer)
+    }
+
+    fn begin_object_value<W: ?Sized + Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(b": ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn plain_arrays_stay_on_one_line() {
+        let value = json!({
+            "tabs": [
+                {"args": ["-x", "test"], "empty": [], "flags": {}},
+                {"args": [], "nested": [[1, 2], [3]]}
+            ],
+            "warnings": ["one", null, true]
+        });
+        assert_eq!(
+            to_string_with_compact_arrays(&value, 2),
+            r#"{
+  "tabs": [
+    {
+      "args": ["-x", "test"],
+      "empty": [],
+      "flags": {}
+    },
+    {
+      "args": [],
+      "nested": [
+        [1, 2],
+        [3]
+      ]
+    }
+  ],
+  "warnings": ["one", null, true]
+}"#
+        );
+    }
+
+    #[test]
+    fn mixed_arrays_are_spread_over_lines() {
+        let value = json!({
+            "tiled_commands": ["CWD: /tmp", {"command": "vim", "cwd": "/tmp"}, "CWD: /tmp"],
+            "args": ["-x"]
+        });
+        assert_eq!(
+            to_string_with_compact_arrays(&value, 2),
+            r#"{
+  "args": ["-x"],
+  "tiled_commands": [
+    "CWD: /tmp",
+    {
+      "command": "vim",
+      "cwd": "/tmp"
+    },
+    "CWD: /tmp"
+  ]
+}"#
+        );
+    }
+
+    #[test]
+    fn output_parses_back_to_the_same_value() {
+        let value = json!([{"name": "a \"quoted\" name", "sizes": [1.5, -2, 3]}, []]);
+        let json = to_string_with_compact_arrays(&value, 4);
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&json).unwrap(),
+            value
+        );
+        assert!(json.starts_with("[\n    {\n        \"name\""));
+    }
+}
@@ -0,0 +1,173 @@
+//! Tells which layout a session was started from.
+//!
+//! Resurrection layouts don't record the name of the layout they came from, but they keep its
+//! `default_tab_template` and swap layouts as they were. A session is matched to a built-in layout
+//! or a layout of the layout directory by comparing those parts, so sessions that only differ in
+//! their tabs still match.
+
+use std::path::Path;
+
+use crate::{
+    home::default_layout_dir, input::layout::Layout, session_serialization::serialize_layout,
+};
+
+/// The built-in layouts, as given to `--layout`
+const BUILT_IN_LAYOUTS: [&str; 5] = [
+    "default",
+    "compact",
+    "strider",
+    "classic",
+    "disable-status-bar",
+];
+
+#[derive(Debug, Clone, Default)]
+pub struct KnownLayouts {
+    /// The name and fingerprint of each layout, the layouts of the layout directory first
+    layouts: Vec<(String, String)>,
+}
+
+impl KnownLayouts {
+    /// Reads the layouts of the layout directory and the built-in ones. Layouts that fail to
+    /// parse are left out.
+    pub fn load() -> Self {
+        let mut layouts = Vec::new();
+        if let Some(Ok(entries)) = default_layout_dir().map(std::fs::read_dir) {
+            let mut paths: Vec<_> = entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| path.extension().is_some_and(|extension| extension == "kdl"))
+                // Swap layouts sit next to the layouts they belong to
+                .filter(|path| !path.to_string_lossy().ends_with(".swap.kdl"))
+                .collect();
+            paths.sort();
+            layouts.extend(paths.iter().filter_map(|path| user_layout(path)));
+        }
+        layouts.extend(
+            BUILT_IN_LAYOUTS
+                .iter()
+                .filter_map(|name| built_in_layout(name)),
+        );
+        Self { layouts }
+    }
+
+    /// The name of the layout that `layout` was started from, if it is a known one
+    pub fn name_of(&self, layout: &Layout) -> Option<String> {
+        let fingerprint = fingerprint(layout)?;
+        self.layouts
+            .iter()
+            .find(|(_, known)| *known == fingerprint)
+            .map(|(name, _)| name.clone())
+    }
+
+    #[cfg(test)]
+    pub(crate) fn from_layouts(layouts: &[(&str, &Layout)]) -> Self {
+        Self {
+            layouts: layouts
+                .iter()
+                .filter_map(|(name, layout)| Some((name.to_string(), fingerprint(layout)?)))
+                .collect(),
+        }
+    }
+}
+
+fn user_layout(path: &Path) -> Option<(String, String)> {
+    let (path_to_layout, raw_layout, raw_swap_layouts) =
+        Layout::stringified_from_path(path).ok()?;
+    let layout = Layout::from_kdl(
+        &raw_layout,
+        Some(path_to_layout),
+        raw_swap_layouts
+            .as_ref()
+            .map(|(path, raw)| (path.as_str(), raw.as_str())),
+        None,
+    )
+    .ok()?;
+    let name = path.file_stem()?.to_string_lossy().to_string();
+    Some((name, fingerprint(&layout)?))
+}
+
+fn built_in_layout(name: &str) -> Option<(String, String)> {
+    let (path_to_layout, raw_layout, raw_swap_layouts) =
+        Layout::stringified_from_default_assets(Path::new(name)).ok()?;
+    let layout = Layout::from_kdl(
+        &raw_layout,
+        Some(path_to_layout),
+        raw_swap_layouts
+            .as_ref()
+            .map(|(path, raw)| (path.as_str(), raw.as_str())),
+        None,
+    )
+    .ok()?;
+    Some((name.to_owned(), fingerprint(&layout)?))
+}
+
+/// The parts of `layout` that resurrection keeps from the layout a session was started from,
+/// serialized the way resurrection layouts are. Layouts without any of them can't be told apart.
+fn fingerprint(layout: &Layout) -> Option<String> {
+    if layout.template.is_none()
+        && layout.swap_tiled_layouts.is_empty()
+        && layout.swap_floating_layouts.is_empty()
+    {
+        return None;
+    }
+    Some(serialize_layout(&Layout {
+        template: layout.template.clone(),
+        swap_tiled_layouts: layout.swap_tiled_layouts.clone(),
+        swap_floating_layouts: layout.swap_floating_layouts.clone(),
+        ..Default::default()
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEV_LAYOUT: &str = r#"
+        layout {
+            default_tab_template {
+                pane size=1 borderless=true {
+                    plugin location="zellij:tab-bar"
+                }
+                children
+            }
+            tab name="editor" {
+                pane command="nvim"
+            }
+        }
+    "#;
+
+    #[test]
+    fn resurrected_session_matches_its_layout() {
+        let dev = Layout::from_kdl(DEV_LAYOUT, None, None, None).unwrap();
+        let mut resurrected = Layout::from_kdl(&serialize_layout(&dev), None, None, None).unwrap();
+        // Tabs opened since don't change the match
+        resurrected.tabs.push(resurrected.tabs[0].clone());
+        let other = Layout::from_kdl("layout { pane; }", None, None, None).unwrap();
+
+        let known = KnownLayouts::from_layouts(&[("dev", &dev)]);
+        assert_eq!(known.name_of(&resurrected).as_deref(), Some("dev"));
+        assert_eq!(known.name_of(&other), None);
+    }
+
+    #[test]
+    fn built_in_layouts_are_known() {
+        let (_, raw_layout, raw_swap_layouts) =
+            Layout::stringified_from_default_assets(Path::new("compact")).unwrap();
+        let compact = Layout::from_kdl(
+            &raw_layout,
+            None,
+            raw_swap_layouts
+                .as_ref()
+                .map(|(path, raw)| (path.as_str(), raw.as_str())),
+            None,
+        )
+        .unwrap();
+        let resurrected = Layout::from_kdl(&serialize_layout(&compact), None, None, None).unwrap();
+        let known = KnownLayouts {
+            layouts: BUILT_IN_LAYOUTS
+                .iter()
+                .filter_map(|name| built_in_layout(name))
+                .collect(),
+        };
+        assert_eq!(known.name_of(&resurrected).as_deref(), Some("compact"));
+    }
+}
@@ -1,2 +1,10 @@
 #[cfg(not(target_family = "wasm"))]
+pub mod default_filter;
+pub mod duration;
+#[cfg(not(target_family = "wasm"))]
+pub mod json_format;
+#[cfg(not(target_family = "wasm"))]
+pub mod layout_origin;
+#[cfg(not(target_family = "wasm"))]
 pub mod sessions;
+pub mod template;
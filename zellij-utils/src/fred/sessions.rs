@@ -1,270 +1,4449 @@
-use std::{cmp::Reverse, time::Duration};
+use std::{
+    cmp::Reverse,
+    collections::BTreeMap,
+    io::{self, IsTerminal, Write},
+    path::{Path, PathBuf},
+    process,
+    time::{Duration, Instant, SystemTime},
+};
 
-use humantime::format_duration;
-use serde::Serialize;
+use chrono::{DateTime, Local, Utc};
+use directories::BaseDirs;
+use serde::{Deserialize, Serialize};
+use unicode_width::UnicodeWidthStr;
 
 use crate::{
+    cli::{
+        CliArgs, LongListOptions, PorcelainVersion, SessionDisplayOptions, SessionField,
+        SessionSortKey, TabSortKey,
+    },
+    consts::{session_layout_cache_file_name, session_layout_file_in, ASSET_MAP},
     envs,
-    input::layout::{FloatingPaneLayout, Layout, Run, RunPluginOrAlias, TiledPaneLayout},
-    sessions::{get_resurrectable_sessions, get_sessions, resurrection_layout},
+    fred::{
+        default_filter::DefaultFilter,
+        duration::{format_age_words, format_ago, UNIT_SECS},
+        json_format::to_string_with_compact_arrays,
+        layout_origin::KnownLayouts,
+        template::{parse_template, TemplateField, TemplateToken},
+    },
+    input::{
+        command::RunCommand,
+        config::Config,
+        layout::{
+            FloatingPaneLayout, Layout, PluginAlias, Run, RunPluginLocation, RunPluginOrAlias,
+            SplitDirection, SplitSize, TiledPaneLayout,
+        },
+        plugins::PluginAliases,
+    },
+    session_serialization::serialize_layout,
+    sessions::{
+        get_resurrectable_sessions, get_resurrectable_sessions_in, get_sessions,
+        is_generated_session_name, resurrection_layout, resurrection_layout_in,
+    },
 };
 
-#[derive(Serialize, Debug, Clone)]
-struct Session {
+const UNNAMED_TAB_LABEL: &str = "<Unnamed Tab>";
+/// Shown for panes that only start the default shell with `--include-dead-panes`
+const SHELL_LABEL: &str = "Shell";
+/// Shown instead of the directory of panes that only have a working directory with
+/// `--anonymize-commands`
+const ANONYMIZED_PATH: &str = "<redacted>";
+/// Version of the `--envelope` JSON output. Bump it whenever the serialized shape of `Session`,
+/// `Tab` or `MyRun` changes.
+const JSON_FORMAT_VERSION: u32 = 14;
+
+/// A resurrectable session, summarized from its resurrection layout
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Session {
+    /// Position of the session in the sorted and filtered list, starting at 1 (only with
+    /// `--ids`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<usize>,
     name: String,
     tabs: Vec<Tab>,
     timestamp: Duration,
     is_current: bool,
     is_active: bool,
+    /// For active sessions, how long ago their server socket was created. Zellij doesn't track
+    /// input, so this is when the running session was last started or resurrected.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    last_active: Option<Duration>,
+    /// Whether any tab has floating panes, with or without a command
+    #[serde(default)]
+    has_floating: bool,
+    /// Number of panes that resurrecting the session restores, across all tabs
+    #[serde(default)]
+    pane_count: usize,
+    /// Whether the session had a resurrection layout, so that a session without one can be told
+    /// apart from one without tabs. Older dumps default to true, since sessions are only listed
+    /// when their layout file exists.
+    #[serde(default = "has_layout_default")]
+    has_layout: bool,
+    /// The file holding the resurrection layout of the session
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    layout_path: Option<PathBuf>,
+    /// Name of the built-in layout or layout of the layout directory the session was started
+    /// from, if it could be told
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    template: Option<String>,
+    /// How many entries with the name of the session were combined by `--merge-duplicates`, 0
+    /// if it wasn't merged
+    #[serde(default, skip_serializing_if = "is_zero")]
+    merged_from: usize,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    resurrect_command: Option<String>,
 }
 
-#[derive(Serialize, Debug, Clone)]
+/// Serialized with a single `commands` list, unless `split_commands` is set
+#[derive(Deserialize, Debug, Clone)]
 struct Tab {
+    // Older dumps don't have the index and counts, so they fall back to 0
+    #[serde(default)]
+    index: usize,
     name: Option<String>,
-    commands: Vec<MyRun>,
+    /// Directory that the panes of the tab inherit, from the `cwd` of the layout or the tab
+    #[serde(default)]
+    cwd: Option<PathBuf>,
+    // The merged list is read back as tiled commands
+    #[serde(alias = "commands")]
+    tiled_commands: Vec<MyRun>,
+    #[serde(default)]
+    floating_commands: Vec<MyRun>,
+    /// Number of panes, including the ones that don't run anything
+    #[serde(default)]
+    pane_count: usize,
+    #[serde(default)]
+    command_count: usize,
+    /// How deeply the tiled panes are nested in splits, 1 when they all share a single split
+    #[serde(default)]
+    max_depth: usize,
+    /// Whether to serialize the tiled and floating commands as separate lists, set by
+    /// `--merge-floating-into-tiles false`
+    #[serde(skip)]
+    split_commands: bool,
+}
+
+/// The serialized shape of `Tab`
+#[derive(Serialize)]
+struct SerializedTab<'a> {
+    index: usize,
+    name: &'a Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cwd: Option<&'a PathBuf>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    commands: Option<Vec<&'a MyRun>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tiled_commands: Option<&'a [MyRun]>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    floating_commands: Option<&'a [MyRun]>,
+    pane_count: usize,
+    command_count: usize,
+    max_depth: usize,
+}
+
+impl Serialize for Tab {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let split = self.split_commands;
+        SerializedTab {
+            index: self.index,
+            name: &self.name,
+            cwd: self.cwd.as_ref(),
+            commands: (!split).then(|| self.commands().collect()),
+            tiled_commands: split.then_some(self.tiled_commands.as_slice()),
+            floating_commands: split.then_some(self.floating_commands.as_slice()),
+            pane_count: self.pane_count,
+            command_count: self.command_count,
+            max_depth: self.max_depth,
+        }
+        .serialize(serializer)
+    }
 }
 
+/// The run of a pane, with the geometry of tiled panes (only kept for `--geometry`). Panes
+/// without a run start the default shell, they are only kept for `--include-dead-panes`. The
+/// last field is set for the runs of splits, which only give a cwd to the panes in them and
+/// aren't panes themselves.
 #[derive(Debug, Clone)]
-struct MyRun(Run);
+struct MyRun(Option<Run>, Option<PaneGeometry>, bool);
 
-#[derive(Serialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone)]
 struct MyCommand {
     command: String,
     cwd: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    geometry: Option<PaneGeometry>,
 }
 
-pub fn print_session_by_name(session_name: &str, no_formatting: bool) {
-    let sessions = collect_sessions();
-    if let Some(session) = sessions.iter().find(|s| s.name == session_name) {
-        if no_formatting {
-            print_unformatted_session(session);
-        } else {
-            print_session(session);
+/// Where a tiled pane sits in its parent split
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+struct PaneGeometry {
+    /// Direction in which the parent of the pane is split
+    split_direction: SplitDirection,
+    /// Share of the parent the pane takes up, if the layout gives it as a percentage
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    size_percent: Option<usize>,
+    /// Number of columns or rows the pane takes up, if the layout gives a fixed size
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    size_fixed: Option<usize>,
+}
+
+impl PaneGeometry {
+    fn new(split_direction: SplitDirection, split_size: Option<SplitSize>) -> Self {
+        let (size_percent, size_fixed) = match split_size {
+            Some(SplitSize::Percent(percent)) => (Some(percent), None),
+            Some(SplitSize::Fixed(fixed)) => (None, Some(fixed)),
+            None => (None, None),
+        };
+        Self {
+            split_direction,
+            size_percent,
+            size_fixed,
         }
-    } else {
-        println!("No session found with the name {session_name}");
     }
 }
 
-pub fn list_sessions_long(json: bool, no_formatting: bool, reverse: bool) {
-    let mut sessions = collect_sessions();
+/// A session as printed by `--json-lines-pretty`, with the short fields first so that the lines
+/// are easy to scan, and the tabs last
+#[derive(Serialize)]
+struct SessionLine<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    id: Option<usize>,
+    name: &'a str,
+    is_current: bool,
+    is_active: bool,
+    timestamp: Duration,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    last_active: Option<Duration>,
+    has_floating: bool,
+    pane_count: usize,
+    has_layout: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    layout_path: Option<&'a Path>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    template: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    resurrect_command: Option<&'a str>,
+    tabs: &'a [Tab],
+}
 
-    if reverse {
-        sessions.sort_unstable_by_key(|session| session.timestamp);
-    } else {
-        sessions.sort_unstable_by_key(|session| Reverse(session.timestamp));
-    }
+/// The JSON output of `--envelope` and `--json-warnings`
+#[derive(Serialize)]
+struct JsonEnvelope<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<u32>,
+    sessions: &'a [Session],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    warnings: Option<&'a [String]>,
+}
 
-    if json {
-        print_sessions_json(sessions);
-    } else if no_formatting {
-        for session in &sessions {
-            print_unformatted_session(session);
+#[derive(Serialize, Debug, Clone, PartialEq)]
+struct CommandCount {
+    command: String,
+    count: usize,
+}
+
+pub fn print_session_by_name(session_name: &str, options: &SessionDisplayOptions) {
+    let mut out = io::stdout().lock();
+    exit_on_write_error(write_session_by_name(&mut out, session_name, options));
+}
+
+fn write_session_by_name(
+    out: &mut dyn Write,
+    session_name: &str,
+    options: &SessionDisplayOptions,
+) -> io::Result<()> {
+    exit_on_unsupported_options(options);
+    let mut sessions = collect_sessions(options);
+    if options.anonymize {
+        let all_names: Vec<String> = sessions.iter().map(|s| s.name.clone()).collect();
+        sessions.retain(|s| s.name == session_name);
+        anonymize_names(&mut sessions, &all_names, None);
+    }
+    if options.posix_paths && !options.json {
+        for session in &mut sessions {
+            session.use_posix_paths();
         }
+    }
+    let session = sessions
+        .iter()
+        .find(|s| options.anonymize || s.name == session_name);
+    if options.json {
+        // A missing session is printed as `null` so scripts can always parse the output
+        print_json(out, &session, options)
+    } else if let Some(session) = session {
+        print_session(out, session, options, &ColumnWidths::default())
     } else {
-        for session in sessions {
-            print_session(&session);
-        }
+        writeln!(out, "No session found with the name {session_name}")
     }
 }
 
-fn print_sessions_json(sessions: Vec<Session>) {
-    println!(
-        "{}",
-        serde_json::to_string(&sessions).expect("Should always serialize correctly")
-    );
-}
-
-fn print_session(session: &Session) {
-    let unnamed_tab_str = String::from("<Unnamed Tab>");
-    let formatted_session_name = format!("\u{1b}[32;1m{}\u{1b}[m", session.name);
-    let timestamp = format!(
-        "[Created \u{1b}[35;1m{}\u{1b}[m ago]",
-        format_duration(session.timestamp)
-    );
-    let current_text = if session.is_current { " (current)" } else { "" };
-    println!("{} {}{}", formatted_session_name, timestamp, current_text);
-    if session.tabs.is_empty() {
-        // Indent by 2 spaces
-        println!("  No running commands");
-    } else {
-        for tab in &session.tabs {
-            let tab_name: &str = tab.name.as_ref().unwrap_or(&unnamed_tab_str);
-            let formatted_tab_name = format!("\u{1b}[36;1m{}\u{1b}[m", tab_name);
-            println!("{}:", formatted_tab_name);
+/// Exits with an error if writing the output failed, except when the reader went away (e.g.
+/// piping into `head`)
+fn exit_on_write_error(result: io::Result<()>) {
+    match result {
+        Err(e) if e.kind() != io::ErrorKind::BrokenPipe => {
+            eprintln!("Failed to write the output: {}", e);
+            process::exit(1);
+        },
+        _ => {},
+    }
+}
 
-            // Indent by 2 spaces
-            for command in &tab.commands {
-                println!(" {}", display_run(&command.0, true));
-            }
-        }
+/// Exits with an error for options that ask for data the session cache doesn't have, rather than
+/// quietly showing nothing
+fn exit_on_unsupported_options(options: &SessionDisplayOptions) {
+    if options.exit_status {
+        eprintln!(
+            "Exit statuses can't be shown: resurrection layouts don't record how commands exited"
+        );
+        process::exit(1);
+    }
+}
+
+/// Prints the resurrection layout of a session as it was parsed, including the fields that the
+/// session summary leaves out
+pub fn print_raw_layout(session_name: &str, json: bool) {
+    match resurrection_layout(session_name) {
+        Ok(Some(layout)) if json => println!(
+            "{}",
+            serde_json::to_string_pretty(&layout).expect("Should always serialize correctly")
+        ),
+        Ok(Some(layout)) => println!("{:#?}", layout),
+        Ok(None) => {
+            eprintln!("No resurrection layout found for session {session_name}");
+            process::exit(2);
+        },
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        },
     }
-    // Empty line between sessions
-    println!();
 }
 
-fn print_unformatted_session(session: &Session) {
-    let unnamed_tab_str = String::from("<Unnamed Tab>");
-    let current_text = if session.is_current { " (current)" } else { "" };
-    let timestamp = format!("Created {} ago", format_duration(session.timestamp));
-    println!("{} {}{}", session.name, timestamp, current_text);
+/// Writes the resurrection layout of a session to `out_path` as a standalone KDL layout. With a
+/// `tab` (a tab name or index), only that tab is written.
+pub fn export_session(
+    session_name: &str,
+    out_path: &Path,
+    tab: Option<&str>,
+) -> Result<(), String> {
+    let mut layout = resurrection_layout(session_name)?
+        .ok_or_else(|| format!("no resurrection layout found for session {session_name}"))?;
+    if let Some(tab) = tab {
+        layout = single_tab_layout(layout, tab)
+            .ok_or_else(|| format!("no tab {tab} found in session {session_name}"))?;
+    }
+    std::fs::write(out_path, serialize_layout(&layout))
+        .map_err(|e| format!("failed to write {}: {}", out_path.display(), e))
+}
 
-    if session.tabs.is_empty() {
-        println!("No running commands");
-    } else {
-        for tab in &session.tabs {
-            let tab_name = tab.name.as_ref().unwrap_or(&unnamed_tab_str);
-            println!("{tab_name}:");
-            for command in &tab.commands {
-                println!("{}", display_run(&command.0, false));
+/// A layout with only the tab of `layout` named `tab`, or at index `tab` if no tab has that name.
+/// The template and swap layouts are left out, as they belong to the session rather than the tab.
+fn single_tab_layout(layout: Layout, tab: &str) -> Option<Layout> {
+    let position = layout
+        .tabs
+        .iter()
+        .position(|(name, _, _)| name.as_deref() == Some(tab))
+        .or_else(|| tab.parse().ok().filter(|index| *index < layout.tabs.len()))?;
+    let tab = layout.tabs.into_iter().nth(position)?;
+    Some(Layout {
+        tabs: vec![tab],
+        ..Default::default()
+    })
+}
+
+pub fn list_sessions_long(options: &LongListOptions) {
+    if let Some(session_name) = &options.probe {
+        process::exit(probe_session(session_name, options.display.verbose));
+    }
+    let mut options = options.clone();
+    let mut out: Box<dyn Write> = match options.output_file.clone() {
+        Some(path) => {
+            apply_output_file_defaults(&mut options, &path);
+            match create_output_file(&path) {
+                Ok(file) => Box::new(io::BufWriter::new(file)),
+                Err(e) => {
+                    eprintln!("Failed to open output file {}: {}", path.display(), e);
+                    process::exit(1);
+                },
             }
-        }
+        },
+        None => Box::new(io::stdout().lock()),
+    };
+    let started = Instant::now();
+    let mut timings = Timings::default();
+    let result = write_sessions_long(&mut out, &options, &mut timings)
+        .and_then(|all_passed| out.flush().map(|()| all_passed));
+    if options.benchmark {
+        eprint!("{}", timings.report(started.elapsed()));
+    }
+    let all_passed = *result.as_ref().unwrap_or(&true);
+    exit_on_write_error(result.map(|_| ()));
+    if !all_passed {
+        process::exit(1);
     }
 }
 
-fn collect_sessions() -> Vec<Session> {
-    let curr_session = envs::get_session_name().unwrap_or_else(|_| "".into());
-    let active_session_names: Vec<String> = get_sessions()
-        .unwrap()
-        .into_iter()
-        .map(|(name, _timestamp)| name)
-        .collect();
+/// How long the stages of listing sessions took, for `--benchmark`
+#[derive(Debug, Default)]
+struct Timings {
+    /// Finding the running and resurrectable sessions
+    listing: Duration,
+    /// Reading and parsing the resurrection layouts
+    parsing: Duration,
+    parsed_layouts: usize,
+    sorting: Duration,
+    /// When the sessions started being printed, if it got that far
+    rendering_started: Option<Instant>,
+}
 
-    get_resurrectable_sessions()
-        .into_iter()
-        .map(|(name, timestamp)| {
-            let is_active = active_session_names.contains(&name);
-            let is_current = name == curr_session;
-            let layout = resurrection_layout(&name).unwrap();
+impl Timings {
+    fn report(&self, total: Duration) -> String {
+        let rendering = self
+            .rendering_started
+            .map_or(Duration::ZERO, |started| started.elapsed());
+        format!(
+            "listing sessions: {:?}\nparsing {} layouts: {:?}\nsorting: {:?}\nrendering: {:?}\ntotal: {:?}\n",
+            self.listing, self.parsed_layouts, self.parsing, self.sorting, rendering, total
+        )
+    }
+}
 
-            Session::new(name, timestamp, layout, is_current, is_active)
-        })
-        .collect()
+/// Returns the exit code of `--probe` for the session named `session_name`. Only the running
+/// sessions and the names in the session cache are read, so that it stays fast.
+fn probe_session(session_name: &str, verbose: bool) -> i32 {
+    let is_active = get_sessions()
+        .unwrap_or_default()
+        .iter()
+        .any(|(name, _)| name == session_name);
+    let is_resurrectable = get_resurrectable_sessions()
+        .iter()
+        .any(|(name, _)| name == session_name);
+    let (exit_code, state) = probe_result(is_active, is_resurrectable);
+    if verbose {
+        println!("{}: {}", session_name, state);
+    }
+    exit_code
 }
 
-impl Session {
-    fn new(
-        name: String,
-        timestamp: Duration,
-        layout: Option<Layout>,
-        is_current: bool,
-        is_active: bool,
-    ) -> Self {
-        let tabs = if let Some(layout) = layout {
-            layout.tabs
+fn probe_result(is_active: bool, is_resurrectable: bool) -> (i32, &'static str) {
+    if is_active {
+        (0, "active")
+    } else if is_resurrectable {
+        (2, "inactive")
+    } else {
+        (1, "not found")
+    }
+}
+
+/// Output written to a file has no colors, and is JSON if the file ends in `.json` and no other
+/// format was asked for
+fn apply_output_file_defaults(options: &mut LongListOptions, path: &Path) {
+    options.display.mono = true;
+    let is_json_file = path
+        .extension()
+        .is_some_and(|extension| extension == "json");
+    let has_other_format = options.template.is_some()
+        || options.porcelain.is_some()
+        || options.flat_json
+        || options.json_lines_pretty
+        || options.oneline
+        || options.group_by_cwd_root
+        || options.group_by_template
+        || options.tree;
+    if is_json_file && !has_other_format {
+        options.display.json = true;
+    }
+}
+
+fn create_output_file(path: &Path) -> io::Result<std::fs::File> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::File::create(path)
+}
+
+/// Writes the session list to `out`. Returns false when the command should fail, e.g. when a
+/// session doesn't pass the checks of --only-errors and --check-plugins.
+fn write_sessions_long(
+    out: &mut dyn Write,
+    options: &LongListOptions,
+    timings: &mut Timings,
+) -> io::Result<bool> {
+    exit_on_unsupported_options(&options.display);
+    if options.whoami {
+        match envs::get_session_name() {
+            Ok(session_name) => write_session_by_name(out, &session_name, &options.display)?,
+            Err(_) if options.display.json => print_json(out, &None::<Session>, &options.display)?,
+            Err(_) => writeln!(out, "Not in a session")?,
+        }
+        return Ok(true);
+    }
+    // An unreadable store would otherwise look like one without sessions
+    if let Some(dir) = &options.dir {
+        if let Err(e) = std::fs::read_dir(dir) {
+            eprintln!("Failed to read {}: {}", dir.display(), e);
+            process::exit(1);
+        }
+    }
+    let (mut sessions, mut warnings) = if options.from_file.is_empty() {
+        // Matching sessions to layouts parses every known layout, only do it when it is shown
+        let known_layouts =
+            (options.group_by_template || options.display.json || options.json_lines_pretty)
+                .then(KnownLayouts::load);
+        collect_sessions_with_warnings(
+            &options.display,
+            options.dir.as_deref(),
+            known_layouts.as_ref(),
+            timings,
+        )
+    } else {
+        let mut sessions = Vec::new();
+        for path in &options.from_file {
+            match load_sessions_from_file(path) {
+                Ok(loaded) => sessions.extend(loaded),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    process::exit(1);
+                },
+            }
+        }
+        for session in &mut sessions {
+            apply_display_options(session, &options.display);
+        }
+        (sessions, Vec::new())
+    };
+    if options.merge_duplicates {
+        sessions = merge_duplicates(sessions);
+    }
+    // Only the text output is normalized, JSON keeps the paths of the OS
+    if options.display.posix_paths && !options.display.json && !options.json_lines_pretty {
+        for session in &mut sessions {
+            session.use_posix_paths();
+        }
+    }
+    if options.strict {
+        let errors = strict_errors(&sessions, &warnings);
+        if !errors.is_empty() {
+            for error in &errors {
+                eprintln!("Error: {}", error);
+            }
+            return Ok(false);
+        }
+    }
+    let embed_warnings = options.json_warnings && options.display.json;
+    if !embed_warnings {
+        print_warnings(&warnings);
+    }
+    // Sessions with a corrupt layout are dropped while collecting, so find them before filtering
+    let corrupt_sessions = if options.only_errors && options.from_file.is_empty() {
+        find_corrupt_sessions(&sessions, options.dir.as_deref())
+    } else {
+        Vec::new()
+    };
+    let unfiltered_counts = StatusCounts::from_sessions(&sessions);
+    // Pseudonyms are numbered among all sessions, so that filters don't change them
+    let all_names: Vec<String> = options
+        .display
+        .anonymize
+        .then(|| sessions.iter().map(|s| s.name.clone()).collect())
+        .unwrap_or_default();
+    filter_sessions(&mut sessions, options);
+    if options.display.anonymize {
+        anonymize_names(&mut sessions, &all_names, options.dir.as_deref());
+    }
+
+    if options.count {
+        writeln!(out, "{}", sessions.len())?;
+        return Ok(true);
+    }
+
+    if options.count_by_status {
+        let counts = if options.count_all {
+            unfiltered_counts
         } else {
-            Vec::new()
+            StatusCounts::from_sessions(&sessions)
         };
-        let tabs: Vec<Tab> = tabs
-            .into_iter()
-            .map(|(maybe_name, tile, floating_panes)| {
-                Tab::new(maybe_name, tile, floating_panes.into_iter())
+        if options.display.json {
+            print_json(out, &counts, &options.display)?;
+        } else {
+            writeln!(out, "active: {}", counts.active)?;
+            writeln!(out, "inactive: {}", counts.inactive)?;
+            writeln!(out, "current: {}", counts.current)?;
+        }
+        return Ok(true);
+    }
+
+    if options.top_commands {
+        print_top_commands(out, &sessions, &options.display)?;
+        return Ok(true);
+    }
+
+    if options.diff_against_running {
+        print_stale_sessions(out, &sessions, options.dir.as_deref(), &options.display)?;
+        return Ok(true);
+    }
+
+    if options.only_errors || options.check_plugins {
+        let aliases = known_plugin_aliases();
+        let mut broken = corrupt_sessions;
+        broken.extend(sessions.iter().filter_map(|session| {
+            let mut problems = if options.only_errors {
+                session.problems()
+            } else {
+                Vec::new()
+            };
+            problems.extend(session.plugin_problems(&aliases));
+            (!problems.is_empty()).then(|| BrokenSession {
+                name: session.name.clone(),
+                problems,
             })
-            .collect();
-        Self {
-            name,
-            tabs,
-            timestamp,
-            is_current,
-            is_active,
+        }));
+        print_broken_sessions(out, &broken, &options.display)?;
+        return Ok(broken.is_empty());
+    }
+
+    let sorting_started = Instant::now();
+    sort_sessions(&mut sessions, options);
+    timings.sorting = sorting_started.elapsed();
+    timings.rendering_started = Some(Instant::now());
+    // Ids are numbered before --tail and the --max-sessions cap, so they don't depend on them
+    if options.ids || options.id.is_some() {
+        for (index, session) in sessions.iter_mut().enumerate() {
+            session.id = Some(index + 1);
         }
     }
-}
+    if let Some(id) = options.id {
+        sessions.retain(|session| session.id == Some(id));
+        if sessions.is_empty() {
+            eprintln!("No session found with the id {}", id);
+            return Ok(false);
+        }
+    }
+    if let Some(count) = options.tail {
+        let excess = sessions.len().saturating_sub(count);
+        sessions.drain(..excess);
+    }
 
-impl Tab {
-    fn new(
-        name: Option<String>,
-        tile: TiledPaneLayout,
-        floating_panes: impl Iterator<Item = FloatingPaneLayout>,
-    ) -> Self {
-        let mut tile_commands = Vec::new();
-        collect_commands_recursive(tile, &mut tile_commands);
-        let floating_commands = floating_panes.filter_map(|float| float.run);
-        let commands = tile_commands
-            .into_iter()
-            .chain(floating_commands)
-            .map(MyRun)
+    if options.histogram {
+        let counts: Vec<(&str, usize)> = sessions
+            .iter()
+            .map(|session| (session.name.as_str(), session.command_count()))
             .collect();
+        for line in histogram_lines(&counts, terminal_width(), use_color(&options.display)) {
+            writeln!(out, "{}", line)?;
+        }
+        return Ok(true);
+    }
+
+    if options.print_kill_commands {
+        for session in sessions.iter().filter(|session| !session.is_active) {
+            writeln!(out, "zellij delete-session {}", shell_quote(&session.name))?;
+        }
+        return Ok(true);
+    }
+
+    if let Some(template) = &options.exec {
+        exec_for_sessions(template, &sessions, options);
+        return Ok(true);
+    }
+
+    // Batch actions above always see every session, the cap only protects the terminal
+    if !options.all && sessions.len() > options.max_sessions {
+        let warning = format!(
+            "only showing the {} newest of {} sessions, pass --all to show every one",
+            options.max_sessions,
+            sessions.len()
+        );
+        if embed_warnings {
+            warnings.push(warning);
+        } else {
+            print_warnings(&[warning]);
+        }
+        keep_newest(&mut sessions, options.max_sessions);
+    }
+
+    if options.interactive {
+        select_session(&sessions, &options.display);
+        return Ok(true);
+    }
+
+    // The header would break the machine-readable formats, even when a .json --output-file picks
+    // JSON on its own
+    let is_machine_format = options.display.json
+        || options.flat_json
+        || options.template.is_some()
+        || options.porcelain.is_some()
+        || options.json_lines_pretty;
+    if options.header && !is_machine_format {
+        let plural = if sessions.len() == 1 { "" } else { "s" };
+        writeln!(out, "Found {} session{}:", sessions.len(), plural)?;
+    }
 
-        Self { name, commands }
+    if options.flat_json {
+        print_json(out, &flat_rows(&sessions), &options.display)?;
+    } else if options.display.json {
+        if embed_warnings || options.envelope {
+            let output = JsonEnvelope {
+                version: options.envelope.then_some(JSON_FORMAT_VERSION),
+                sessions: &sessions,
+                warnings: embed_warnings.then_some(&warnings),
+            };
+            print_json(out, &output, &options.display)?;
+        } else {
+            print_json(out, &sessions, &options.display)?;
+        }
+    } else if let Some(template) = &options.template {
+        let tokens = parse_template(template).expect("Template is validated by the CLI");
+        for session in &sessions {
+            writeln!(
+                out,
+                "{}",
+                render_template(&tokens, session, &options.display)
+            )?;
+        }
+    } else if let Some(version) = options.porcelain {
+        for session in &sessions {
+            writeln!(out, "{}", porcelain_line(session, version))?;
+        }
+    } else if options.json_lines_pretty {
+        for session in &sessions {
+            let line = SessionLine {
+                id: session.id,
+                name: &session.name,
+                is_current: session.is_current,
+                is_active: session.is_active,
+                timestamp: session.timestamp,
+                last_active: session.last_active,
+                has_floating: session.has_floating,
+                pane_count: session.pane_count,
+                has_layout: session.has_layout,
+                layout_path: session.layout_path.as_deref(),
+                template: session.template.as_deref(),
+                resurrect_command: session.resurrect_command.as_deref(),
+                tabs: &session.tabs,
+            };
+            writeln!(
+                out,
+                "{}",
+                serde_json::to_string(&line).expect("Should always serialize correctly")
+            )?;
+        }
+    } else if options.oneline {
+        for session in &sessions {
+            print_session_oneline(out, session, options)?;
+        }
+    } else if options.group_by_cwd_root {
+        print_sessions_by_cwd_root(out, &sessions, options)?;
+    } else if options.group_by_template {
+        print_sessions_by_template(out, &sessions, options)?;
+    } else if options.tree {
+        let glyphs = if options.ascii || !locale_is_utf8() {
+            &ASCII_TREE_GLYPHS
+        } else {
+            &UNICODE_TREE_GLYPHS
+        };
+        for session in &sessions {
+            for line in render_session_tree(session, &options.display, glyphs) {
+                writeln!(out, "{}", line)?;
+            }
+        }
+    } else {
+        // Names are padded to the widest one so that the timestamps form a column
+        let widths = if options.align {
+            ColumnWidths::of(&sessions, &options.display)
+        } else {
+            ColumnWidths::default()
+        };
+        for session in sessions {
+            print_session(out, &session, &options.display, &widths)?;
+        }
     }
+    Ok(true)
 }
 
-impl Serialize for MyRun {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: serde::Serializer,
-    {
-        match &self.0 {
-            // For commands, include the CWD
-            Run::Command(run_command) => {
-                let command = format!(
-                    "{} {}",
-                    run_command.command.to_string_lossy(),
-                    run_command.args.join(" ")
+/// Runs `template` in a shell for every session, with `{name}` replaced by the session name
+fn exec_for_sessions(template: &str, sessions: &[Session], options: &LongListOptions) {
+    if looks_destructive(template) && !options.yes {
+        eprintln!(
+            "Refusing to run \"{}\" since it looks destructive, pass --yes to run it anyway",
+            template
+        );
+        process::exit(1);
+    }
+    let mut failed = false;
+    for session in sessions {
+        let command = exec_command_for(template, &session.name);
+        // Progress goes to stderr, so that it doesn't mix with what the commands print
+        if !options.quiet {
+            eprintln!("Running: {}", command);
+        }
+        match process::Command::new("sh").arg("-c").arg(&command).status() {
+            Ok(status) if status.success() => {},
+            Ok(status) => {
+                eprintln!(
+                    "Command for session {} failed with {}",
+                    session.name, status
                 );
+                failed = true;
+            },
+            Err(e) => {
+                eprintln!("Failed to run command for session {}: {}", session.name, e);
+                failed = true;
+            },
+        }
+    }
+    if failed {
+        process::exit(1);
+    }
+}
 
-                let cwd = match &run_command.cwd {
-                    Some(cwd) => cwd.to_string_lossy().to_string(),
-                    None => String::new(),
-                };
+fn exec_command_for(template: &str, session_name: &str) -> String {
+    template.replace("{name}", &shell_quote(session_name))
+}
 
-                let my_command = MyCommand { command, cwd };
-                my_command.serialize(serializer)
+/// Whether `command` contains a word that suggests it deletes or kills something
+fn looks_destructive(command: &str) -> bool {
+    const DESTRUCTIVE_WORDS: [&str; 5] = ["delete", "kill", "rm", "remove", "rmdir"];
+    command
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .any(|word| DESTRUCTIVE_WORDS.contains(&word))
+}
+
+fn select_session(sessions: &[Session], options: &SessionDisplayOptions) {
+    if sessions.is_empty() {
+        eprintln!("No sessions found.");
+        return;
+    }
+    for (i, session) in sessions.iter().enumerate() {
+        println!(
+            "{}: {}{}",
+            i,
+            session.name,
+            session_markers(session, options)
+        );
+    }
+    let stdin = io::stdin();
+    loop {
+        print!("Select a session: ");
+        let _ = io::stdout().flush();
+        let mut input = String::new();
+        match stdin.read_line(&mut input) {
+            // EOF, nothing was selected
+            Ok(0) | Err(_) => return,
+            Ok(_) => {},
+        }
+        match input.trim().parse::<usize>() {
+            Ok(index) if index < sessions.len() => {
+                println!();
+                let mut out = io::stdout().lock();
+                exit_on_write_error(print_session(
+                    &mut out,
+                    &sessions[index],
+                    options,
+                    &ColumnWidths::default(),
+                ));
+                return;
             },
-            // For all other types of Run, display as normal
-            // TODO: Custom serializers for each type
-            // e.g. {
-            //  cwd: "foo/bar/baz",
-            //  type: "cwd"
-            // }
-            other => serializer.serialize_str(&display_run(other, false)),
+            _ => eprintln!("Please enter a number between 0 and {}", sessions.len() - 1),
         }
     }
 }
 
-fn display_run(run: &Run, should_format: bool) -> String {
-    let format_title = if should_format {
-        |title| format!("\u{1b}[35;1m{}\u{1b}[m", title)
+fn sort_sessions(sessions: &mut [Session], options: &LongListOptions) {
+    let (sort_key, reverse) = if options.reverse_alpha {
+        (SessionSortKey::Name, true)
     } else {
-        |title| String::from(title)
+        (options.sort, options.reverse)
     };
-
-    match run {
-        Run::Command(run_command) => {
-            format!(
-                "{} {} {}",
-                format_title("Running:"),
-                run_command.command.to_string_lossy(),
-                run_command.args.join(" ")
-            )
-        },
-        Run::EditFile(path_buf, _, _) => {
-            format!("{} {}", format_title("File:"), path_buf.to_string_lossy())
-        },
-        Run::Cwd(path_buf) => {
-            format!("{} {}", format_title("CWD:"), path_buf.to_string_lossy())
+    // The timestamp is the age of a session, so the newest sessions have the smallest one
+    let by_creation = |a: &Session, b: &Session| {
+        if options.oldest_first {
+            b.timestamp.cmp(&a.timestamp)
+        } else {
+            a.timestamp.cmp(&b.timestamp)
+        }
+    };
+    // Ties are broken by name so that the order is the same from run to run
+    match sort_key {
+        SessionSortKey::Created => {
+            sessions.sort_unstable_by(|a, b| by_creation(a, b).then_with(|| a.name.cmp(&b.name)))
         },
-        Run::Plugin(plugin) => format!(
-            "{} {}",
-            format_title("Plugin:"),
-            display_plugin_or_alias(plugin)
-        ),
+        SessionSortKey::Name => sessions.sort_unstable_by(|a, b| a.name.cmp(&b.name)),
+        SessionSortKey::Active => sessions.sort_unstable_by(|a, b| {
+            b.is_active
+                .cmp(&a.is_active)
+                .then_with(|| by_creation(a, b))
+                .then_with(|| a.name.cmp(&b.name))
+        }),
+        SessionSortKey::Cwd => sessions.sort_by_cached_key(|session| {
+            let cwd = session.primary_cwd();
+            // `false` sorts first
+            let is_placed_last = cwd.is_none() != options.missing_cwd_first;
+            (is_placed_last, cwd, session.name.clone())
+        }),
+    }
+    if reverse {
+        sessions.reverse();
     }
 }
 
-fn display_plugin_or_alias(plugin_or_alias: &RunPluginOrAlias) -> String {
-    match plugin_or_alias {
-        RunPluginOrAlias::RunPlugin(run_plugin) => run_plugin.location.to_string(),
-        RunPluginOrAlias::Alias(plugin_alias) => plugin_alias.name.to_string(),
-    }
+fn render_template(
+    tokens: &[TemplateToken],
+    session: &Session,
+    options: &SessionDisplayOptions,
+) -> String {
+    tokens
+        .iter()
+        .map(|token| match token {
+            TemplateToken::Literal(literal) => literal.clone(),
+            TemplateToken::Field(TemplateField::Name) => session.name.clone(),
+            TemplateToken::Field(TemplateField::Age) => format_age(session.timestamp, options),
+            TemplateToken::Field(TemplateField::Tabs) => session.tabs.len().to_string(),
+            TemplateToken::Field(TemplateField::Commands) => session.command_count().to_string(),
+            TemplateToken::Field(TemplateField::Current) => {
+                if session.is_current {
+                    options.current_marker.clone()
+                } else {
+                    String::new()
+                }
+            },
+        })
+        .collect()
 }
 
-fn collect_commands_recursive(tile: TiledPaneLayout, buf: &mut Vec<Run>) {
-    for child in tile.children {
-        if let Some(run) = &child.run {
-            buf.push(run.clone());
+fn filter_sessions(sessions: &mut Vec<Session>, options: &LongListOptions) {
+    if !options.no_default_filter {
+        match DefaultFilter::load() {
+            Ok(Some(default_filter)) => {
+                sessions.retain(|session| default_filter.matches(&session.name))
+            },
+            Ok(None) => {},
+            Err(e) => {
+                eprintln!("Failed to load the default session filter: {}", e);
+                process::exit(1);
+            },
         }
-        collect_commands_recursive(child, buf);
+    }
+    if let Some(name) = &options.name {
+        sessions.retain(|session| name_matches(&session.name, name, options.exact));
+    }
+    if let Some(path) = &options.filter_file {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) => {
+                eprintln!("Failed to read filter file {}: {}", path.display(), e);
+                process::exit(1);
+            },
+        };
+        let patterns = parse_filter_patterns(&contents);
+        sessions.retain(|session| {
+            patterns
+                .iter()
+                .any(|pattern| name_matches(&session.name, pattern, false))
+        });
+    }
+    // Tabs go first so that the other filters only look at the tabs that are shown
+    if let Some(tab_filter) = &options.tab_filter {
+        for session in sessions.iter_mut() {
+            session.tabs.retain(|tab| {
+                tab.name
+                    .as_ref()
+                    .is_some_and(|name| name.contains(tab_filter.as_str()))
+            });
+        }
+        sessions.retain(|session| !session.tabs.is_empty());
+    }
+    if options.only_floating || options.only_tiled {
+        for tab in sessions.iter_mut().flat_map(|session| &mut session.tabs) {
+            if options.only_floating {
+                tab.tiled_commands.clear();
+            } else {
+                tab.floating_commands.clear();
+            }
+            tab.command_count = tab
+                .commands()
+                .filter(|command| command.is_command())
+                .count();
+        }
+        if !options.show_empty {
+            sessions.retain(|session| session.command_count() > 0);
+        }
+    }
+    if let Some(plugin) = &options.plugin {
+        sessions.retain(|session| session.uses_plugin(plugin));
+    }
+    if let Some(path) = &options.has_editfile {
+        sessions.retain(|session| session.edits_file(path.as_deref()));
+    }
+    if let Some(pattern) = &options.command_grep {
+        sessions.retain(|session| session.has_run_matching(pattern, options.command_only));
+    }
+    if options.named_only {
+        sessions.retain(|session| !is_generated_session_name(&session.name));
+    }
+    if options.older_than_active {
+        // With no active sessions, every inactive session is a candidate
+        let oldest_active = sessions
+            .iter()
+            .filter(|session| session.is_active)
+            .map(|session| session.timestamp)
+            .max();
+        sessions.retain(|session| {
+            !session.is_active && oldest_active.map_or(true, |oldest| session.timestamp > oldest)
+        });
+    }
+    if let Some(path) = &options.newer_than_file {
+        // Without the file, this is the first run and every session is new
+        match std::fs::metadata(path).and_then(|metadata| metadata.modified()) {
+            Ok(modified) => {
+                let now = SystemTime::now();
+                sessions
+                    .retain(|session| created_within(session.timestamp, now, Some(modified), None));
+            },
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {},
+            Err(e) => {
+                eprintln!(
+                    "Failed to read the modification time of {}: {}",
+                    path.display(),
+                    e
+                );
+                process::exit(1);
+            },
+        }
+    }
+    if options.after.is_some() || options.before.is_some() {
+        let now = SystemTime::now();
+        sessions.retain(|session| {
+            created_within(session.timestamp, now, options.after, options.before)
+        });
+    }
+}
+
+/// Whether a session name matches a `--name` pattern, by substring unless `exact`
+fn name_matches(name: &str, pattern: &str, exact: bool) -> bool {
+    if exact {
+        name == pattern
+    } else {
+        name.contains(pattern)
+    }
+}
+
+/// The name patterns of a `--filter-file`, one per line, without blank lines and `#` comments
+fn parse_filter_patterns(contents: &str) -> Vec<&str> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .collect()
+}
+
+/// Whether a session created `age` before `now` was created in the `[after, before)` window
+fn created_within(
+    age: Duration,
+    now: SystemTime,
+    after: Option<SystemTime>,
+    before: Option<SystemTime>,
+) -> bool {
+    let created = now.checked_sub(age).unwrap_or(SystemTime::UNIX_EPOCH);
+    after.map_or(true, |after| created >= after) && before.map_or(true, |before| created < before)
+}
+
+/// Drops all but the `count` most recently created sessions, keeping their order
+fn keep_newest(sessions: &mut Vec<Session>, count: usize) {
+    let mut ages: Vec<Duration> = sessions.iter().map(|session| session.timestamp).collect();
+    ages.sort_unstable();
+    let Some(&max_age) = ages.get(count.saturating_sub(1)) else {
+        sessions.clear();
+        return;
+    };
+    // Sessions as old as the cutoff are kept in order until there are enough of them
+    let mut remaining_at_max_age = count - ages.iter().filter(|age| **age < max_age).count();
+    sessions.retain(|session| match session.timestamp.cmp(&max_age) {
+        std::cmp::Ordering::Less => true,
+        std::cmp::Ordering::Equal if remaining_at_max_age > 0 => {
+            remaining_at_max_age -= 1;
+            true
+        },
+        _ => false,
+    });
+}
+
+/// Widths that `print_session` pads its columns to with `--align`, zero for no padding
+#[derive(Debug, Default, PartialEq)]
+struct ColumnWidths {
+    name: usize,
+    /// The ages only get columns in plain output, where they are shortened by `format_age_column`
+    created: usize,
+    last_active: usize,
+}
+
+impl ColumnWidths {
+    fn of(sessions: &[Session], options: &SessionDisplayOptions) -> Self {
+        let name = sessions.iter().map(Session::name_width).max().unwrap_or(0);
+        if !options.no_formatting {
+            return Self {
+                name,
+                ..Self::default()
+            };
+        }
+        let age_width = |age| format_age_column(age, options).width();
+        Self {
+            name,
+            created: sessions
+                .iter()
+                .map(|session| age_width(session.timestamp))
+                .max()
+                .unwrap_or(0),
+            last_active: sessions
+                .iter()
+                .filter_map(|session| session.last_active.map(age_width))
+                .max()
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// Calls `f` with the session name, tab name and `Run` of every command in every session.
+pub fn visit_commands<F: FnMut(&str, Option<&str>, &Run)>(f: F) {
+    let sessions = collect_sessions(&SessionDisplayOptions::default());
+    visit_session_commands(&sessions, f);
+}
+
+fn visit_session_commands<F: FnMut(&str, Option<&str>, &Run)>(sessions: &[Session], mut f: F) {
+    for session in sessions {
+        for tab in &session.tabs {
+            for run in tab.commands().filter_map(|command| command.0.as_ref()) {
+                f(&session.name, tab.name.as_deref(), run);
+            }
+        }
+    }
+}
+
+/// A command of `--flat-json`, with the session and tab it belongs to
+#[derive(Serialize, Debug, PartialEq)]
+struct FlatRow<'a> {
+    session: &'a str,
+    session_created: Duration,
+    tab: Option<&'a str>,
+    command: String,
+    /// The directory of the command, or of its tab if it doesn't have one
+    cwd: Option<&'a Path>,
+    is_active: bool,
+}
+
+fn flat_rows(sessions: &[Session]) -> Vec<FlatRow> {
+    let mut rows = Vec::new();
+    for session in sessions {
+        for tab in &session.tabs {
+            for command in tab.commands() {
+                let (text, cwd) = match &command.0 {
+                    Some(Run::Command(run_command)) => {
+                        let mut text = run_command.command.to_string_lossy().to_string();
+                        for arg in &run_command.args {
+                            text.push(' ');
+                            text.push_str(arg);
+                        }
+                        (text, run_command.cwd.as_deref())
+                    },
+                    _ => (command.display(false, false, false), None),
+                };
+                rows.push(FlatRow {
+                    session: &session.name,
+                    session_created: session.timestamp,
+                    tab: tab.name.as_deref(),
+                    command: text,
+                    cwd: cwd.or(tab.cwd.as_deref()),
+                    is_active: session.is_active,
+                });
+            }
+        }
+    }
+    rows
+}
+
+/// How many sessions are in each state, for `--count-by-status`
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+struct StatusCounts {
+    active: usize,
+    inactive: usize,
+    current: usize,
+}
+
+impl StatusCounts {
+    fn from_sessions(sessions: &[Session]) -> Self {
+        let active = sessions.iter().filter(|session| session.is_active).count();
+        Self {
+            active,
+            inactive: sessions.len() - active,
+            current: sessions.iter().filter(|session| session.is_current).count(),
+        }
+    }
+}
+
+/// A session with at least one problem found by `--only-errors`
+#[derive(Serialize, Debug, PartialEq)]
+struct BrokenSession {
+    name: String,
+    problems: Vec<String>,
+}
+
+/// The sessions in the store, or in `dir` if given, whose resurrection layout can't be parsed,
+/// which are missing from the `collected` ones
+fn find_corrupt_sessions(collected: &[Session], dir: Option<&Path>) -> Vec<BrokenSession> {
+    let sessions = match dir {
+        Some(dir) => get_resurrectable_sessions_in(dir),
+        None => get_resurrectable_sessions(),
+    };
+    sessions
+        .into_iter()
+        .filter(|(name, _)| !collected.iter().any(|session| session.name == *name))
+        .filter_map(|(name, _)| {
+            let error = match dir {
+                Some(dir) => resurrection_layout_in(dir, &name),
+                None => resurrection_layout(&name),
+            }
+            .err()?;
+            Some(BrokenSession {
+                name,
+                problems: vec![format!("corrupt layout: {}", error)],
+            })
+        })
+        .collect()
+}
+
+fn print_broken_sessions(
+    out: &mut dyn Write,
+    sessions: &[BrokenSession],
+    options: &SessionDisplayOptions,
+) -> io::Result<()> {
+    if options.json {
+        return print_json(out, &sessions, options);
+    }
+    let should_color = use_color(options);
+    for session in sessions {
+        writeln!(out, "{}:", colorize(&session.name, "31;1", should_color))?;
+        for problem in &session.problems {
+            writeln!(out, "    {}", problem)?;
+        }
+    }
+    Ok(())
+}
+
+/// What makes `--strict` fail: the problems of collecting the sessions followed by the problems
+/// and plugin problems of each session
+fn strict_errors(sessions: &[Session], warnings: &[String]) -> Vec<String> {
+    let aliases = known_plugin_aliases();
+    let mut errors = warnings.to_vec();
+    for session in sessions {
+        errors.extend(
+            session
+                .problems()
+                .into_iter()
+                .chain(session.plugin_problems(&aliases))
+                .map(|problem| format!("session {}: {}", session.name, problem)),
+        );
+    }
+    errors
+}
+
+/// The plugin aliases of the user's configuration, or the default ones if it can't be read
+fn known_plugin_aliases() -> PluginAliases {
+    Config::try_from(&CliArgs::default())
+        .or_else(|_| Config::from_default_assets())
+        .map(|config| config.plugins)
+        .unwrap_or_default()
+}
+
+/// Why the plugin `plugin_or_alias` can't be loaded, if it can be told without loading it.
+/// Remote plugins are never flagged, as checking them would mean downloading them.
+fn plugin_problem(plugin_or_alias: &RunPluginOrAlias, aliases: &PluginAliases) -> Option<String> {
+    let is_found = match plugin_or_alias {
+        RunPluginOrAlias::RunPlugin(run_plugin) => match &run_plugin.location {
+            RunPluginLocation::File(path) => path.is_file(),
+            RunPluginLocation::Zellij(tag) => {
+                ASSET_MAP.contains_key(&PathBuf::from("plugins").join(format!("{}.wasm", tag)))
+            },
+            RunPluginLocation::Remote(_) => true,
+        },
+        RunPluginOrAlias::Alias(plugin_alias) => aliases.aliases.contains_key(&plugin_alias.name),
+    };
+    (!is_found).then(|| {
+        format!(
+            "plugin not found: {}",
+            display_plugin_or_alias(plugin_or_alias)
+        )
+    })
+}
+
+/// Whether `program` can be run from `cwd`, either as a path or by looking it up in `PATH`
+fn is_executable_found(program: &Path, cwd: Option<&Path>) -> bool {
+    if program.components().count() > 1 {
+        return match cwd {
+            Some(cwd) => cwd.join(program).is_file(),
+            None => program.is_file(),
+        };
+    }
+    std::env::var_os("PATH")
+        .is_some_and(|paths| std::env::split_paths(&paths).any(|dir| dir.join(program).is_file()))
+}
+
+/// An active session whose resurrection layout was saved before it was last started
+#[derive(Serialize, Debug, PartialEq)]
+struct StaleSession<'a> {
+    name: &'a str,
+    /// How long ago the resurrection layout was last written
+    layout_saved: Duration,
+    /// How long ago the running session was started
+    running_since: Duration,
+}
+
+/// Lists the active sessions whose resurrection layout predates the running session, so that
+/// resurrecting them would lose what changed since. The cache only holds the saved layout, so
+/// the panes of the running session can't be compared directly.
+fn print_stale_sessions(
+    out: &mut dyn Write,
+    sessions: &[Session],
+    dir: Option<&Path>,
+    options: &SessionDisplayOptions,
+) -> io::Result<()> {
+    let stale: Vec<StaleSession> = sessions
+        .iter()
+        .filter_map(|session| {
+            let running_since = session.last_active?;
+            let layout_saved = layout_saved_age(dir, &session.name)?;
+            is_stale(layout_saved, running_since).then_some(StaleSession {
+                name: &session.name,
+                layout_saved,
+                running_since,
+            })
+        })
+        .collect();
+    if options.json {
+        return print_json(out, &stale, options);
+    }
+    let should_color = use_color(options);
+    for session in &stale {
+        writeln!(
+            out,
+            "{}: layout saved {}, session running since {}",
+            colorize(session.name, "32;1", should_color),
+            format_when(session.layout_saved, options, false),
+            format_when(session.running_since, options, false),
+        )?;
+    }
+    Ok(())
+}
+
+/// How long ago the resurrection layout of a session was last written
+fn layout_saved_age(dir: Option<&Path>, session_name: &str) -> Option<Duration> {
+    let modified = std::fs::metadata(session_layout_file(dir, session_name))
+        .and_then(|metadata| metadata.modified())
+        .ok()?;
+    Some(Duration::from_secs(modified.elapsed().ok()?.as_secs()))
+}
+
+/// Whether a layout saved `layout_saved` ago is older than a session started `running_since` ago
+fn is_stale(layout_saved: Duration, running_since: Duration) -> bool {
+    layout_saved > running_since
+}
+
+fn print_top_commands(
+    out: &mut dyn Write,
+    sessions: &[Session],
+    options: &SessionDisplayOptions,
+) -> io::Result<()> {
+    let counts = count_commands(sessions);
+    if options.json {
+        return print_json(out, &counts, options);
+    }
+    let count_width = counts.first().map_or(0, |c| c.count.to_string().len());
+    for CommandCount { command, count } in counts {
+        let count = format!("{:>count_width$}", count);
+        writeln!(
+            out,
+            "{} {}",
+            colorize(&count, "35;1", use_color(options)),
+            command
+        )?;
+    }
+    Ok(())
+}
+
+/// One line per labeled count with a bar of `#` as long as the count relative to the largest
+/// one, so that the widest line fits in `width` columns. Non-zero counts always get a bar.
+fn histogram_lines(counts: &[(&str, usize)], width: usize, should_color: bool) -> Vec<String> {
+    let label_width = counts
+        .iter()
+        .map(|(label, _)| label.width())
+        .max()
+        .unwrap_or(0);
+    let max_count = counts.iter().map(|(_, count)| *count).max().unwrap_or(0);
+    let count_width = max_count.to_string().len();
+    // The label and count are separated from the bar by a space each
+    let bar_width = width.saturating_sub(label_width + count_width + 2).max(1);
+    counts
+        .iter()
+        .map(|(label, count)| {
+            let bar_len = if *count == 0 {
+                0
+            } else {
+                (count * bar_width / max_count).max(1)
+            };
+            let padding = " ".repeat(label_width - label.width());
+            let bar = format!("{:<bar_width$}", "#".repeat(bar_len));
+            format!(
+                "{}{} {} {:>count_width$}",
+                label,
+                padding,
+                colorize(&bar, "32", should_color),
+                count
+            )
+        })
+        .collect()
+}
+
+/// Width of the terminal on stdout, falling back to `$COLUMNS` and then 80 columns
+fn terminal_width() -> usize {
+    use nix::libc::{ioctl, winsize, STDOUT_FILENO, TIOCGWINSZ};
+
+    let mut size = winsize {
+        ws_row: 0,
+        ws_col: 0,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+    // TIOCGWINSZ is an u32, but the second argument to ioctl is u64 on some platforms
+    #[allow(clippy::useless_conversion)]
+    let result = unsafe { ioctl(STDOUT_FILENO, TIOCGWINSZ.into(), &mut size) };
+    if result == 0 && size.ws_col != 0 {
+        return size.ws_col as usize;
+    }
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok())
+        .unwrap_or(80)
+}
+
+/// Tallies each program run across `sessions`, most frequent first
+fn count_commands(sessions: &[Session]) -> Vec<CommandCount> {
+    let mut counts: BTreeMap<String, usize> = BTreeMap::new();
+    visit_session_commands(sessions, |_, _, run| {
+        if let Run::Command(run_command) = run {
+            let program = run_command.command.to_string_lossy().to_string();
+            *counts.entry(program).or_default() += 1;
+        }
+    });
+    let mut counts: Vec<CommandCount> = counts
+        .into_iter()
+        .map(|(command, count)| CommandCount { command, count })
+        .collect();
+    // Stable sort so that programs with the same count stay in alphabetical order
+    counts.sort_by_key(|c| Reverse(c.count));
+    counts
+}
+
+fn print_json<T: Serialize>(
+    out: &mut dyn Write,
+    value: &T,
+    options: &SessionDisplayOptions,
+) -> io::Result<()> {
+    let json = if options.tabs_as_json_object {
+        // Going through a `Value` sorts the fields, so the default output avoids it
+        let mut value = serde_json::to_value(value).expect("Should always serialize correctly");
+        key_tabs_by_name(&mut value);
+        to_json_string(&value, options)
+    } else {
+        to_json_string(value, options)
+    };
+    if options.no_trailing_newline {
+        write!(out, "{}", json)?;
+        out.flush()
+    } else {
+        writeln!(out, "{}", json)
+    }
+}
+
+fn to_json_string<T: Serialize>(value: &T, options: &SessionDisplayOptions) -> String {
+    if options.json_compact_arrays {
+        to_string_with_compact_arrays(value, options.indent)
+    } else {
+        serde_json::to_string(value).expect("Should always serialize correctly")
+    }
+}
+
+/// Replaces the `tabs` array of every session in `value` with an object keyed by tab name. Tabs
+/// without a name, or with the name of an earlier tab, are keyed by their index instead.
+fn key_tabs_by_name(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Array(sessions) => sessions.iter_mut().for_each(key_tabs_by_name),
+        // The sessions of the `--envelope` and `--json-warnings` output
+        serde_json::Value::Object(output) if output.contains_key("sessions") => {
+            if let Some(sessions) = output.get_mut("sessions") {
+                key_tabs_by_name(sessions);
+            }
+        },
+        serde_json::Value::Object(session) => {
+            if let Some(serde_json::Value::Array(tabs)) = session.get_mut("tabs") {
+                let mut keyed_tabs = serde_json::Map::new();
+                for (index, tab) in tabs.drain(..).enumerate() {
+                    let key = match tab.get("name").and_then(|name| name.as_str()) {
+                        Some(name) if !keyed_tabs.contains_key(name) => name.to_owned(),
+                        _ => index.to_string(),
+                    };
+                    keyed_tabs.insert(key, tab);
+                }
+                session.insert("tabs".to_owned(), serde_json::Value::Object(keyed_tabs));
+            }
+        },
+        _ => {},
+    }
+}
+
+fn print_session(
+    out: &mut dyn Write,
+    session: &Session,
+    options: &SessionDisplayOptions,
+    widths: &ColumnWidths,
+) -> io::Result<()> {
+    let should_format = !options.no_formatting;
+    let should_color = use_color(options);
+    // Plain output is not indented so that it stays easy to parse
+    let indent = if should_format {
+        " ".repeat(options.indent)
+    } else {
+        String::new()
+    };
+    let shows = |field| options.show.is_empty() || options.show.contains(&field);
+    let times = if widths.created > 0 {
+        // A table of right-aligned ages, without the words around them
+        let mut columns = Vec::new();
+        if shows(SessionField::Created) {
+            let age = format_age_column(session.timestamp, options);
+            columns.push(format!("{:>1$}", age, widths.created));
+        }
+        if widths.last_active > 0 && shows(SessionField::LastActive) {
+            let last_active = session
+                .last_active
+                .map(|age| format_age_column(age, options))
+                .unwrap_or_default();
+            columns.push(format!("{:>1$}", last_active, widths.last_active));
+        }
+        // Ages have spaces in them, so the columns are set further apart
+        columns.join("  ")
+    } else {
+        let mut times = Vec::new();
+        if shows(SessionField::Created) {
+            let age = format_when(session.timestamp, options, should_color);
+            times.push(format!("Created {}", age));
+        }
+        if let Some(last_active) = session
+            .last_active
+            .filter(|_| shows(SessionField::LastActive))
+        {
+            let last_active = format_when(last_active, options, should_color);
+            let label = if times.is_empty() {
+                "Last active"
+            } else {
+                "last active"
+            };
+            times.push(format!("{} {}", label, last_active));
+        }
+        times.join(", ")
+    };
+    let mut header = Vec::new();
+    if shows(SessionField::Name) {
+        let padding = " ".repeat(widths.name.saturating_sub(session.name_width()));
+        header.push(format!(
+            "{}{}",
+            format_session_name(session, options),
+            padding
+        ));
+    }
+    if !times.is_empty() {
+        header.push(if should_format {
+            format!("[{}]", times)
+        } else {
+            times
+        });
+    }
+    let mut header = header.join(" ");
+    if shows(SessionField::Markers) {
+        header.push_str(&session_markers(session, options));
+    }
+    if !header.is_empty() {
+        writeln!(out, "{}", header.trim())?;
+    }
+    if let Some(resurrect_command) = &session.resurrect_command {
+        writeln!(
+            out,
+            "{}{} {}",
+            indent,
+            colorize("Resurrect:", "35;1", should_color),
+            resurrect_command
+        )?;
+    }
+    if options.layout_path {
+        if let Some(layout_path) = &session.layout_path {
+            writeln!(
+                out,
+                "{}{} {}",
+                indent,
+                colorize("Layout:", "35;1", should_color),
+                layout_path.display()
+            )?;
+        }
+    }
+    if options.pane_count && session.has_layout {
+        let plural = if session.pane_count == 1 { "" } else { "s" };
+        writeln!(
+            out,
+            "{}{} {} pane{}",
+            indent,
+            colorize("Restores", "35;1", should_color),
+            session.pane_count,
+            plural
+        )?;
+    }
+    if options.verbose && session.has_floating {
+        writeln!(
+            out,
+            "{}{}",
+            indent,
+            colorize("Has floating panes", "35;1", should_color)
+        )?;
+    }
+    if session.merged_from > 0 {
+        writeln!(
+            out,
+            "{}{} {} entries",
+            indent,
+            colorize("Merged from", "35;1", should_color),
+            session.merged_from
+        )?;
+    }
+    if !shows(SessionField::Tabs) || (options.collapse_current && session.is_current) {
+        // The tabs are left out
+    } else if !session.has_layout {
+        writeln!(out, "{}No resurrection layout", indent)?;
+    } else if session.tabs.is_empty() {
+        writeln!(out, "{}No running commands", indent)?;
+    } else {
+        for tab in &session.tabs {
+            let formatted_tab_name = colorize(tab.display_name(options), "36;1", should_color);
+            let depth = if options.tab_depth {
+                format!(" (depth {})", tab.max_depth)
+            } else {
+                String::new()
+            };
+            if options.tab_index {
+                writeln!(out, "{}: {}{}:", tab.index, formatted_tab_name, depth)?;
+            } else {
+                writeln!(out, "{}{}:", formatted_tab_name, depth)?;
+            }
+            if let Some(cwd) = &tab.cwd {
+                writeln!(
+                    out,
+                    "{}{} {}",
+                    indent,
+                    colorize("cwd:", "35", should_color),
+                    cwd.to_string_lossy()
+                )?;
+            }
+
+            if options.merge_floating_into_tiles {
+                print_commands(out, tab.commands(), tab.cwd.as_deref(), &indent, options)?;
+            } else {
+                let nested_indent = indent.repeat(2);
+                for (label, commands) in [
+                    ("Tiled:", &tab.tiled_commands),
+                    ("Floating:", &tab.floating_commands),
+                ] {
+                    writeln!(out, "{}{}", indent, colorize(label, "36", should_color))?;
+                    print_commands(
+                        out,
+                        commands.iter(),
+                        tab.cwd.as_deref(),
+                        &nested_indent,
+                        options,
+                    )?;
+                }
+            }
+        }
+    }
+    if should_format {
+        // Empty line between sessions
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// Prints `commands` one per line. Panes that only inherit `tab_cwd` are left out, as the tab's
+/// "cwd:" line already shows it.
+fn print_commands<'a>(
+    out: &mut dyn Write,
+    commands: impl Iterator<Item = &'a MyRun>,
+    tab_cwd: Option<&Path>,
+    indent: &str,
+    options: &SessionDisplayOptions,
+) -> io::Result<()> {
+    let should_format = !options.no_formatting;
+    let inherits_tab_cwd = |command: &MyRun| match (&command.0, tab_cwd) {
+        (Some(Run::Cwd(cwd)), Some(tab_cwd)) => cwd == tab_cwd,
+        _ => false,
+    };
+    for command in commands.filter(|command| !inherits_tab_cwd(command)) {
+        let line = command.display(
+            use_color(options),
+            options.plugin_url_full,
+            options.highlight_commands,
+        );
+        match &command.0 {
+            // Commands always get the column, even without a cwd, so it can be cut
+            Some(Run::Command(run_command)) if options.show_cwd && !should_format => {
+                let cwd = run_command
+                    .cwd
+                    .as_ref()
+                    .map(|cwd| cwd.to_string_lossy())
+                    .unwrap_or_default();
+                writeln!(out, "{}{}\t{}", indent, line, cwd)?;
+            },
+            _ => writeln!(out, "{}{}", indent, line)?,
+        }
+    }
+    Ok(())
+}
+
+/// The current and active markers that apply to `session`, each preceded by a space
+fn session_markers(session: &Session, options: &SessionDisplayOptions) -> String {
+    let current_marker = session
+        .is_current
+        .then_some(options.current_marker.as_str());
+    let active_marker = options
+        .active_marker
+        .as_deref()
+        .filter(|_| session.is_active);
+    let mut markers = String::new();
+    for marker in [current_marker, active_marker].into_iter().flatten() {
+        if !marker.is_empty() {
+            markers.push(' ');
+            markers.push_str(marker);
+        }
+    }
+    markers
+}
+
+/// When something happened `age` ago: "3h 2m ago", or the time it happened with
+/// `--absolute-time`
+fn format_when(age: Duration, options: &SessionDisplayOptions, should_color: bool) -> String {
+    if options.absolute_time {
+        let time = format_time(time_before_now(age), options);
+        colorize(&time, "35;1", should_color)
+    } else {
+        let age = colorize(&format_age(age, options), "35;1", should_color);
+        format_ago(&age, options.lang)
+    }
+}
+
+/// The point in time `age` before now. Ages are durations relative to when the sessions were
+/// read, so this is where they are turned into absolute times.
+fn time_before_now(age: Duration) -> SystemTime {
+    SystemTime::now()
+        .checked_sub(age)
+        .unwrap_or(SystemTime::UNIX_EPOCH)
+}
+
+/// Formats `time` with `--time-format`, in the local time zone unless `--utc` is given
+fn format_time(time: SystemTime, options: &SessionDisplayOptions) -> String {
+    let time = DateTime::<Utc>::from(time);
+    if options.utc {
+        time.format(&options.time_format).to_string()
+    } else {
+        time.with_timezone(&Local)
+            .format(&options.time_format)
+            .to_string()
+    }
+}
+
+fn format_age(age: Duration, options: &SessionDisplayOptions) -> String {
+    if options.raw_time {
+        return age.as_secs().to_string();
+    }
+    let age = match options.precision {
+        Some(precision) => truncate_duration(age, precision),
+        None => age,
+    };
+    format_age_words(age, options.lang, options.abbrev)
+}
+
+/// An age for the columns of plain `--align` output: abbreviated to its two largest units (or
+/// `--precision` ones), unless ages are shown as times or seconds
+fn format_age_column(age: Duration, options: &SessionDisplayOptions) -> String {
+    if options.absolute_time {
+        format_time(time_before_now(age), options)
+    } else if options.raw_time {
+        age.as_secs().to_string()
+    } else {
+        let age = truncate_duration(age, options.precision.unwrap_or(2));
+        format_age_words(age, options.lang, true)
+    }
+}
+
+/// Drops everything below the `precision` largest time units of `duration`, so that with a
+/// precision of 2, "2days 3h 4m 5s" becomes "2days 3h"
+fn truncate_duration(duration: Duration, precision: usize) -> Duration {
+    let secs = duration.as_secs();
+    match UNIT_SECS.iter().position(|unit| secs >= *unit) {
+        Some(largest) => {
+            let smallest = UNIT_SECS[(largest + precision.max(1) - 1).min(UNIT_SECS.len() - 1)];
+            Duration::from_secs(secs - secs % smallest)
+        },
+        None => Duration::ZERO,
+    }
+}
+
+fn print_session_oneline(
+    out: &mut dyn Write,
+    session: &Session,
+    options: &LongListOptions,
+) -> io::Result<()> {
+    let should_color = use_color(&options.display);
+    let tabs: Vec<String> = session
+        .tabs
+        .iter()
+        .map(|tab| {
+            let commands: Vec<String> = tab
+                .commands()
+                .map(|command| {
+                    command.display(
+                        should_color,
+                        options.display.plugin_url_full,
+                        options.display.highlight_commands,
+                    )
+                })
+                .collect();
+            let tab_name = colorize(tab.display_name(&options.display), "36;1", should_color);
+            join_labeled(&tab_name, &commands, &options.cmd_sep)
+        })
+        .collect();
+    let session_name = format_session_name(session, &options.display);
+    writeln!(
+        out,
+        "{}",
+        join_labeled(&session_name, &tabs, &options.tab_sep)
+    )
+}
+
+const UNKNOWN_ROOT_LABEL: &str = "(unknown)";
+
+/// Prints the sessions under a header for the project they belong to, with the sessions whose
+/// project can't be told last
+fn print_sessions_by_cwd_root(
+    out: &mut dyn Write,
+    sessions: &[Session],
+    options: &LongListOptions,
+) -> io::Result<()> {
+    let mut groups: BTreeMap<Option<PathBuf>, Vec<&Session>> = BTreeMap::new();
+    for session in sessions {
+        let roots: Vec<PathBuf> = session
+            .cwds()
+            .into_iter()
+            .map(|cwd| cwd_root(&cwd, options.cwd_root_depth))
+            .collect();
+        groups.entry(common_root(&roots)).or_default().push(session);
+    }
+    // `None` sorts first in the map, but the unknown group reads better at the end
+    let unknown = groups.remove(&None);
+    let groups = groups
+        .into_iter()
+        .map(|(root, sessions)| {
+            let label = root.map_or_else(String::new, |root| root.to_string_lossy().to_string());
+            (label, sessions)
+        })
+        .chain(unknown.map(|sessions| (UNKNOWN_ROOT_LABEL.to_owned(), sessions)));
+    print_session_groups(out, groups, options)
+}
+
+const AD_HOC_LABEL: &str = "(ad-hoc)";
+
+/// Prints the sessions under a header for the layout they were started from, with the sessions
+/// that didn't come from a known layout last
+fn print_sessions_by_template(
+    out: &mut dyn Write,
+    sessions: &[Session],
+    options: &LongListOptions,
+) -> io::Result<()> {
+    let mut groups: BTreeMap<Option<&str>, Vec<&Session>> = BTreeMap::new();
+    for session in sessions {
+        groups
+            .entry(session.template.as_deref())
+            .or_default()
+            .push(session);
+    }
+    let ad_hoc = groups.remove(&None);
+    let groups = groups
+        .into_iter()
+        .map(|(template, sessions)| (template.unwrap_or_default().to_owned(), sessions))
+        .chain(ad_hoc.map(|sessions| (AD_HOC_LABEL.to_owned(), sessions)));
+    print_session_groups(out, groups, options)
+}
+
+fn print_session_groups<'a>(
+    out: &mut dyn Write,
+    groups: impl Iterator<Item = (String, Vec<&'a Session>)>,
+    options: &LongListOptions,
+) -> io::Result<()> {
+    let should_color = use_color(&options.display);
+    for (label, sessions) in groups {
+        writeln!(out, "{}", colorize(&label, "34;1", should_color))?;
+        for session in sessions {
+            print_session(out, session, &options.display, &ColumnWidths::default())?;
+        }
+    }
+    Ok(())
+}
+
+/// The project directory of `cwd`: its first `depth` directories if given, or else the closest
+/// directory with a git repository, or else `cwd` itself
+fn cwd_root(cwd: &Path, depth: Option<usize>) -> PathBuf {
+    match depth {
+        Some(depth) => {
+            let mut normal_components = 0;
+            cwd.components()
+                .take_while(|component| {
+                    if let std::path::Component::Normal(_) = component {
+                        normal_components += 1;
+                    }
+                    normal_components <= depth
+                })
+                .collect()
+        },
+        None => cwd
+            .ancestors()
+            .find(|dir| dir.join(".git").exists())
+            .unwrap_or(cwd)
+            .to_path_buf(),
+    }
+}
+
+/// The longest path that all of `paths` start with, `None` if there are no paths
+fn common_root(paths: &[PathBuf]) -> Option<PathBuf> {
+    let (first, rest) = paths.split_first()?;
+    let mut root = first.clone();
+    for path in rest {
+        while !path.starts_with(&root) {
+            if !root.pop() {
+                break;
+            }
+        }
+    }
+    Some(root)
+}
+
+struct TreeGlyphs {
+    branch: &'static str,
+    last_branch: &'static str,
+    continuation: &'static str,
+}
+
+const UNICODE_TREE_GLYPHS: TreeGlyphs = TreeGlyphs {
+    branch: "├─ ",
+    last_branch: "└─ ",
+    continuation: "│  ",
+};
+
+const ASCII_TREE_GLYPHS: TreeGlyphs = TreeGlyphs {
+    branch: "+- ",
+    last_branch: "`- ",
+    continuation: "|  ",
+};
+
+/// Whether the locale, as set in the environment, uses UTF-8
+fn locale_is_utf8() -> bool {
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|value| !value.is_empty())
+        .is_some_and(|locale| {
+            let locale = locale.to_lowercase();
+            locale.contains("utf-8") || locale.contains("utf8")
+        })
+}
+
+fn render_session_tree(
+    session: &Session,
+    options: &SessionDisplayOptions,
+    glyphs: &TreeGlyphs,
+) -> Vec<String> {
+    let should_color = use_color(options);
+    let age = format_when(session.timestamp, options, should_color);
+    let mut lines = vec![format!(
+        "{} [Created {}]{}",
+        format_session_name(session, options),
+        age,
+        session_markers(session, options)
+    )];
+    if options.collapse_current && session.is_current {
+        return lines;
+    }
+    for (i, tab) in session.tabs.iter().enumerate() {
+        let is_last_tab = i + 1 == session.tabs.len();
+        let (tab_branch, prefix) = if is_last_tab {
+            (
+                glyphs.last_branch,
+                " ".repeat(glyphs.continuation.chars().count()),
+            )
+        } else {
+            (glyphs.branch, glyphs.continuation.to_owned())
+        };
+        lines.push(format!(
+            "{}{}",
+            tab_branch,
+            colorize(tab.display_name(options), "36;1", should_color)
+        ));
+        let commands: Vec<&MyRun> = tab.commands().collect();
+        for (j, command) in commands.iter().enumerate() {
+            let branch = if j + 1 == commands.len() {
+                glyphs.last_branch
+            } else {
+                glyphs.branch
+            };
+            lines.push(format!(
+                "{}{}{}",
+                prefix,
+                branch,
+                command.display(
+                    should_color,
+                    options.plugin_url_full,
+                    options.highlight_commands
+                )
+            ));
+        }
+    }
+    lines
+}
+
+/// Formats `session` for `--porcelain`. The output of a released version must never change.
+fn porcelain_line(session: &Session, version: PorcelainVersion) -> String {
+    match version {
+        PorcelainVersion::V1 => format!(
+            "{}\t{}\t{}\t{}\t{}\t{}",
+            session.name,
+            session.timestamp.as_secs(),
+            session.is_active,
+            session.is_current,
+            session.tabs.len(),
+            session.command_count()
+        ),
+    }
+}
+
+/// Joins `items` after a `label: ` prefix, leaving out the trailing space when there are none
+fn join_labeled(label: &str, items: &[String], separator: &str) -> String {
+    if items.is_empty() {
+        format!("{}:", label)
+    } else {
+        format!("{}: {}", label, items.join(separator))
+    }
+}
+
+/// Colors used for the parts of a session that depend on its state
+struct SessionTheme<'a> {
+    active_name: &'a str,
+    inactive_name: &'a str,
+}
+
+impl<'a> SessionTheme<'a> {
+    fn from_options(options: &'a SessionDisplayOptions) -> Self {
+        Self {
+            active_name: &options.active_name_color,
+            inactive_name: &options.inactive_name_color,
+        }
+    }
+
+    fn name_color(&self, session: &Session) -> &'a str {
+        if session.is_active {
+            self.active_name
+        } else {
+            self.inactive_name
+        }
+    }
+}
+
+/// The session name colored by whether the session is active. Formatted output without colors
+/// marks active sessions with "(active)" instead.
+fn format_session_name(session: &Session, options: &SessionDisplayOptions) -> String {
+    let name = if use_color(options) {
+        let theme = SessionTheme::from_options(options);
+        colorize(&session.name, theme.name_color(session), true)
+    } else if !options.no_formatting && session.is_active {
+        format!("{} (active)", session.name)
+    } else {
+        session.name.clone()
+    };
+    match session.id {
+        Some(id) => format!("{}: {}", id, name),
+        None => name,
+    }
+}
+
+/// Whether to color the output, which `--no-formatting`, `--mono` and the `NO_COLOR` environment
+/// variable all turn off
+fn use_color(options: &SessionDisplayOptions) -> bool {
+    let no_color = std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+    !options.no_formatting && !options.mono && !no_color
+}
+
+fn colorize(text: &str, color: &str, should_color: bool) -> String {
+    if should_color {
+        format!("\u{1b}[{}m{}\u{1b}[m", color, text)
+    } else {
+        text.to_owned()
+    }
+}
+
+/// Combines the sessions with the same name into the first one of them
+fn merge_duplicates(sessions: Vec<Session>) -> Vec<Session> {
+    let mut merged: Vec<Session> = Vec::new();
+    for session in sessions {
+        match merged.iter_mut().find(|merged| merged.name == session.name) {
+            Some(existing) => existing.merge(session),
+            None => merged.push(session),
+        }
+    }
+    merged
+}
+
+fn is_zero(count: &usize) -> bool {
+    *count == 0
+}
+
+fn is_false(flag: &bool) -> bool {
+    !flag
+}
+
+/// Reads sessions back from a file written by `--json`, with or without `--envelope`
+fn load_sessions_from_file(path: &Path) -> Result<Vec<Session>, String> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum SavedSessions {
+        Bare(Vec<Session>),
+        Envelope { sessions: Vec<Session> },
+    }
+
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    match serde_json::from_str(&contents) {
+        Ok(SavedSessions::Bare(sessions)) | Ok(SavedSessions::Envelope { sessions }) => {
+            Ok(sessions)
+        },
+        Err(e) => Err(format!(
+            "Failed to parse sessions from {}: {}",
+            path.display(),
+            e
+        )),
+    }
+}
+
+/// Summarizes every resurrectable session, in no particular order. Problems with individual
+/// sessions are printed to stderr.
+pub fn collect_sessions(options: &SessionDisplayOptions) -> Vec<Session> {
+    let (sessions, warnings) =
+        collect_sessions_with_warnings(options, None, None, &mut Timings::default());
+    print_warnings(&warnings);
+    sessions
+}
+
+fn print_warnings(warnings: &[String]) {
+    for warning in warnings {
+        eprintln!("Warning: {}", warning);
+    }
+}
+
+/// Like `collect_sessions`, but returns the problems instead of printing them. The sessions are
+/// read from `dir` if given, a session_info folder of another user whose sessions can't be ours
+/// or running. Sessions only get a template if `known_layouts` are given.
+fn collect_sessions_with_warnings(
+    options: &SessionDisplayOptions,
+    dir: Option<&Path>,
+    known_layouts: Option<&KnownLayouts>,
+    timings: &mut Timings,
+) -> (Vec<Session>, Vec<String>) {
+    let listing_started = Instant::now();
+    let mut warnings = Vec::new();
+    let curr_session = envs::get_session_name().unwrap_or_else(|_| "".into());
+    // The resurrectable sessions are still worth listing if the running ones can't be queried.
+    // Our sockets say nothing about the sessions of another user's `dir`.
+    let active_sessions: Vec<(String, Duration)> = match dir.is_none().then(get_sessions) {
+        Some(Ok(sessions)) => sessions,
+        Some(Err(e)) => {
+            warnings.push(format!(
+                "failed to query running sessions ({:?}), no session will be shown as active",
+                e
+            ));
+            Vec::new()
+        },
+        None => Vec::new(),
+    };
+
+    let show_progress = options.progress && !options.json && io::stderr().is_terminal();
+    let resurrectable_sessions = match dir {
+        Some(dir) => get_resurrectable_sessions_in(dir),
+        None => get_resurrectable_sessions(),
+    };
+    timings.listing = listing_started.elapsed();
+    let total = resurrectable_sessions.len();
+    let sessions = resurrectable_sessions
+        .into_iter()
+        .enumerate()
+        .filter_map(|(i, (name, timestamp))| {
+            let last_active = active_sessions
+                .iter()
+                .find(|(active_name, _)| *active_name == name)
+                .map(|(_, age)| *age);
+            let is_current = dir.is_none() && name == curr_session;
+            let parsing_started = Instant::now();
+            let layout = match dir {
+                Some(dir) => resurrection_layout_in(dir, &name),
+                None => resurrection_layout(&name),
+            };
+            timings.parsing += parsing_started.elapsed();
+            timings.parsed_layouts += 1;
+            if show_progress {
+                eprint!("\rLoaded {}/{} sessions…", i + 1, total);
+            }
+            let layout = match layout {
+                Ok(layout) => layout,
+                Err(e) => {
+                    warnings.push(format!("skipped session {}: {}", name, e));
+                    return None;
+                },
+            };
+            let resurrect_command = options
+                .resurrect_command
+                .then(|| resurrect_command_for(dir, &name));
+            let template = known_layouts
+                .zip(layout.as_ref())
+                .and_then(|(known_layouts, layout)| known_layouts.name_of(layout));
+
+            let mut session = Session::new(
+                name,
+                timestamp,
+                layout,
+                is_current,
+                last_active,
+                resurrect_command,
+            );
+            session.layout_path = Some(session_layout_file(dir, &session.name));
+            session.template = template;
+            apply_display_options(&mut session, options);
+            Some(session)
+        })
+        .collect();
+    if show_progress {
+        // Clear the progress line so that it doesn't mix with the output
+        eprint!("\r\u{1b}[K");
+    }
+    (sessions, warnings)
+}
+
+/// Replaces the names of `sessions` with "session-N", numbered by the sorted order of
+/// `all_names` so that a session keeps its pseudonym whichever sessions are shown. The sessions
+/// were read from `dir`, if given.
+fn anonymize_names(sessions: &mut [Session], all_names: &[String], dir: Option<&Path>) {
+    let mut sorted_names: Vec<&str> = all_names.iter().map(String::as_str).collect();
+    sorted_names.sort_unstable();
+    for session in sessions {
+        if let Ok(position) = sorted_names.binary_search(&session.name.as_str()) {
+            session.name = format!("session-{}", position + 1);
+            // The path has the real name in it
+            session.layout_path = None;
+            if session.resurrect_command.is_some() {
+                session.resurrect_command = Some(resurrect_command_for(dir, &session.name));
+            }
+        }
+    }
+}
+
+/// Applies the options that change the contents of a session rather than how it is printed, so
+/// that every output format shows the same thing
+fn apply_display_options(session: &mut Session, options: &SessionDisplayOptions) {
+    if options.redact_cwd {
+        if let Some(base_dirs) = BaseDirs::new() {
+            session.redact_home(base_dirs.home_dir());
+        }
+    }
+    if options.strip_ansi_from_names {
+        session.strip_ansi();
+    }
+    if options.anonymize_commands {
+        session.anonymize_commands();
+    }
+    if let Some(max_arg_len) = options.max_arg_len {
+        session.truncate_args(max_arg_len);
+    }
+    if options.sort_commands {
+        session.sort_commands();
+    }
+    if let Some(sort_key) = options.sort_tabs {
+        session.sort_tabs(sort_key);
+    }
+    if !options.geometry {
+        session.clear_geometry();
+    }
+    if !options.include_dead_panes {
+        session.remove_shells();
+    }
+    for tab in &mut session.tabs {
+        tab.split_commands = !options.merge_floating_into_tiles;
+    }
+}
+
+/// The resurrection layout of a session, in `dir` if given or in our own cache otherwise
+fn session_layout_file(dir: Option<&Path>, session_name: &str) -> PathBuf {
+    match dir {
+        Some(dir) => session_layout_file_in(dir, session_name),
+        None => session_layout_cache_file_name(session_name),
+    }
+}
+
+fn resurrect_command_for(dir: Option<&Path>, session_name: &str) -> String {
+    format!(
+        "zellij --layout {} attach --create {}",
+        shell_quote(&session_layout_file(dir, session_name).to_string_lossy()),
+        shell_quote(session_name)
+    )
+}
+
+/// Quotes `s` for a POSIX shell if it contains anything but plainly safe characters
+fn shell_quote(s: &str) -> String {
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "-_./:@+=%,".contains(c);
+    if !s.is_empty() && s.chars().all(is_safe) {
+        s.to_owned()
+    } else {
+        format!("'{}'", s.replace('\'', r"'\''"))
+    }
+}
+
+fn has_layout_default() -> bool {
+    true
+}
+
+impl Session {
+    fn new(
+        name: String,
+        timestamp: Duration,
+        layout: Option<Layout>,
+        is_current: bool,
+        last_active: Option<Duration>,
+        resurrect_command: Option<String>,
+    ) -> Self {
+        let has_layout = layout.is_some();
+        let tabs = if let Some(layout) = layout {
+            layout.tabs
+        } else {
+            Vec::new()
+        };
+        let has_floating = tabs
+            .iter()
+            .any(|(_name, _tile, floating_panes)| !floating_panes.is_empty());
+        let tabs: Vec<Tab> = tabs
+            .into_iter()
+            .enumerate()
+            .map(|(index, (maybe_name, tile, floating_panes))| {
+                Tab::new(index, maybe_name, tile, floating_panes.into_iter())
+            })
+            .collect();
+        let pane_count = tabs.iter().map(|tab| tab.pane_count).sum();
+        Self {
+            id: None,
+            name,
+            tabs,
+            timestamp,
+            is_current,
+            is_active: last_active.is_some(),
+            last_active,
+            has_floating,
+            pane_count,
+            has_layout,
+            layout_path: None,
+            template: None,
+            merged_from: 0,
+            resurrect_command,
+        }
+    }
+
+    /// How long ago the session was created, as reported by the session cache. This is a
+    /// duration relative to the moment the session was read, not an absolute point in time.
+    pub fn age(&self) -> Duration {
+        self.timestamp
+    }
+
+    /// Width of the name in the terminal, including the `--ids` prefix
+    fn name_width(&self) -> usize {
+        let id_width = self.id.map_or(0, |id| format!("{}: ", id).len());
+        id_width + self.name.width()
+    }
+
+    /// Whether any tab runs a plugin whose location or alias contains `plugin`
+    fn uses_plugin(&self, plugin: &str) -> bool {
+        self.tabs
+            .iter()
+            .flat_map(Tab::commands)
+            .any(|command| match &command.0 {
+                Some(Run::Plugin(plugin_or_alias)) => {
+                    display_plugin_or_alias(plugin_or_alias).contains(plugin)
+                },
+                _ => false,
+            })
+    }
+
+    /// Whether any pane edits a file, whose path contains `path` if it is given
+    fn edits_file(&self, path: Option<&str>) -> bool {
+        self.tabs
+            .iter()
+            .flat_map(Tab::commands)
+            .any(|command| match &command.0 {
+                Some(Run::EditFile(file, _, _)) => {
+                    path.map_or(true, |path| file.to_string_lossy().contains(path))
+                },
+                _ => false,
+            })
+    }
+
+    /// Whether any run displays as text containing `pattern`
+    fn has_run_matching(&self, pattern: &str, command_only: bool) -> bool {
+        self.tabs
+            .iter()
+            .flat_map(Tab::commands)
+            .filter(|command| !command_only || matches!(command.0, Some(Run::Command(_))))
+            .any(|command| command.display(false, false, false).contains(pattern))
+    }
+
+    fn command_count(&self) -> usize {
+        self.tabs
+            .iter()
+            .flat_map(Tab::commands)
+            .filter(|command| command.is_command())
+            .count()
+    }
+
+    fn sort_commands(&mut self) {
+        for tab in &mut self.tabs {
+            for commands in [&mut tab.tiled_commands, &mut tab.floating_commands] {
+                commands.sort_by_cached_key(|command| command.display(false, false, false));
+            }
+        }
+    }
+
+    /// Sorts the tabs, keeping the layout order between tabs that compare equal. Their index
+    /// stays the one in the layout.
+    fn sort_tabs(&mut self, sort_key: TabSortKey) {
+        match sort_key {
+            TabSortKey::Count => self.tabs.sort_by_key(|tab| Reverse(tab.command_count)),
+            // Unnamed tabs go last
+            TabSortKey::Name => self.tabs.sort_by(|a, b| match (&a.name, &b.name) {
+                (Some(a), Some(b)) => a.cmp(b),
+                (a, b) => b.is_some().cmp(&a.is_some()),
+            }),
+        }
+    }
+
+    /// Adds the tabs and commands of `other`, a session with the same name, keeping the details
+    /// of whichever of the two was created last
+    fn merge(&mut self, mut other: Session) {
+        let merged_from = self.merged_from.max(1) + other.merged_from.max(1);
+        if other.timestamp < self.timestamp {
+            std::mem::swap(self, &mut other);
+        }
+        self.merged_from = merged_from;
+        self.is_current |= other.is_current;
+        self.is_active |= other.is_active;
+        self.last_active = match (self.last_active, other.last_active) {
+            (Some(ours), Some(theirs)) => Some(ours.min(theirs)),
+            (ours, theirs) => ours.or(theirs),
+        };
+        self.has_floating |= other.has_floating;
+        self.has_layout |= other.has_layout;
+        self.layout_path = self.layout_path.take().or(other.layout_path);
+        self.template = self.template.take().or(other.template);
+        self.resurrect_command = self.resurrect_command.take().or(other.resurrect_command);
+        for tab in other.tabs {
+            // Unnamed tabs can only be told apart by their position
+            let same_tab = self.tabs.iter_mut().find(|existing| match &tab.name {
+                Some(name) => existing.name.as_ref() == Some(name),
+                None => existing.name.is_none() && existing.index == tab.index,
+            });
+            match same_tab {
+                Some(existing) => existing.merge(tab),
+                None => {
+                    let index = self.tabs.len();
+                    self.tabs.push(Tab { index, ..tab });
+                },
+            }
+        }
+        self.pane_count = self.tabs.iter().map(|tab| tab.pane_count).sum();
+    }
+
+    /// Drops the panes that only start the default shell
+    fn remove_shells(&mut self) {
+        for tab in &mut self.tabs {
+            for commands in [&mut tab.tiled_commands, &mut tab.floating_commands] {
+                commands.retain(|command| command.0.is_some());
+            }
+        }
+    }
+
+    fn clear_geometry(&mut self) {
+        for tab in &mut self.tabs {
+            for command in &mut tab.tiled_commands {
+                command.1 = None;
+            }
+        }
+    }
+
+    /// Problems that would keep the session from resurrecting as saved: working directories that
+    /// no longer exist and programs that can't be found. Relative directories are skipped, since
+    /// what they are relative to depends on where the session is resurrected from.
+    fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        for cwd in self.cwds() {
+            let problem = format!("missing directory: {}", cwd.display());
+            if cwd.is_absolute() && !cwd.is_dir() && !problems.contains(&problem) {
+                problems.push(problem);
+            }
+        }
+        for command in self.tabs.iter().flat_map(Tab::commands) {
+            if let Some(Run::Command(run_command)) = &command.0 {
+                let problem = format!("command not found: {}", run_command.command.display());
+                if !is_executable_found(&run_command.command, run_command.cwd.as_deref())
+                    && !problems.contains(&problem)
+                {
+                    problems.push(problem);
+                }
+            }
+        }
+        problems
+    }
+
+    /// Plugins of the session that would fail to load when it is resurrected
+    fn plugin_problems(&self, aliases: &PluginAliases) -> Vec<String> {
+        let mut problems = Vec::new();
+        for command in self.tabs.iter().flat_map(Tab::commands) {
+            if let Some(Run::Plugin(plugin_or_alias)) = &command.0 {
+                if let Some(problem) = plugin_problem(plugin_or_alias, aliases) {
+                    if !problems.contains(&problem) {
+                        problems.push(problem);
+                    }
+                }
+            }
+        }
+        problems
+    }
+
+    /// The working directory that the session is most likely about: the first one of a tab or
+    /// command
+    fn primary_cwd(&self) -> Option<PathBuf> {
+        self.cwds().into_iter().next()
+    }
+
+    /// Every working directory that the tabs and commands of the session mention
+    fn cwds(&self) -> Vec<PathBuf> {
+        let mut cwds = Vec::new();
+        for tab in &self.tabs {
+            cwds.extend(tab.cwd.clone());
+            for command in tab.commands() {
+                match &command.0 {
+                    Some(Run::Command(run_command)) => cwds.extend(run_command.cwd.clone()),
+                    Some(Run::EditFile(_, _, cwd)) => cwds.extend(cwd.clone()),
+                    Some(Run::Cwd(cwd)) => cwds.push(cwd.clone()),
+                    Some(Run::Plugin(_)) | None => {},
+                }
+            }
+        }
+        cwds
+    }
+
+    /// Removes escape sequences and other control characters from the names and runs, so that
+    /// printing them can't mess with the terminal
+    fn strip_ansi(&mut self) {
+        self.name = strip_ansi(&self.name);
+        for tab in &mut self.tabs {
+            tab.name = tab.name.as_deref().map(strip_ansi);
+            tab.cwd = tab.cwd.as_deref().map(strip_ansi_from_path);
+            for command in tab
+                .tiled_commands
+                .iter_mut()
+                .chain(tab.floating_commands.iter_mut())
+            {
+                command.strip_ansi();
+            }
+        }
+    }
+
+    fn truncate_args(&mut self, max_len: usize) {
+        for tab in &mut self.tabs {
+            for command in tab
+                .tiled_commands
+                .iter_mut()
+                .chain(tab.floating_commands.iter_mut())
+            {
+                if let Some(Run::Command(run_command)) = &mut command.0 {
+                    for arg in &mut run_command.args {
+                        *arg = truncate_arg(arg, max_len);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Drops the working directories and arguments of the session and keeps only the file name of
+    /// programs and edited files, so that the layout can be shared without private paths
+    fn anonymize_commands(&mut self) {
+        for tab in &mut self.tabs {
+            tab.cwd = None;
+            for command in tab
+                .tiled_commands
+                .iter_mut()
+                .chain(tab.floating_commands.iter_mut())
+            {
+                command.anonymize();
+            }
+        }
+    }
+
+    fn use_posix_paths(&mut self) {
+        if let Some(layout_path) = &mut self.layout_path {
+            *layout_path = to_posix_path(layout_path);
+        }
+        for cwd in self.tabs.iter_mut().filter_map(|tab| tab.cwd.as_mut()) {
+            *cwd = to_posix_path(cwd);
+        }
+        for command in self.tabs.iter_mut().flat_map(|tab| {
+            tab.tiled_commands
+                .iter_mut()
+                .chain(tab.floating_commands.iter_mut())
+        }) {
+            command.use_posix_paths();
+        }
+    }
+
+    fn redact_home(&mut self, home: &Path) {
+        if let Some(layout_path) = &mut self.layout_path {
+            *layout_path = redact_home(layout_path, home);
+        }
+        for cwd in self.tabs.iter_mut().filter_map(|tab| tab.cwd.as_mut()) {
+            *cwd = redact_home(cwd, home);
+        }
+        for command in self.tabs.iter_mut().flat_map(|tab| {
+            tab.tiled_commands
+                .iter_mut()
+                .chain(tab.floating_commands.iter_mut())
+        }) {
+            command.redact_home(home);
+        }
+    }
+}
+
+impl Tab {
+    fn new(
+        index: usize,
+        name: Option<String>,
+        tile: TiledPaneLayout,
+        floating_panes: impl Iterator<Item = FloatingPaneLayout>,
+    ) -> Self {
+        // The root of the tab isn't a pane, its run only carries the cwd the panes inherit
+        let cwd = match &tile.run {
+            Some(Run::Cwd(cwd)) => Some(cwd.clone()),
+            _ => None,
+        };
+        let mut tile_commands = Vec::new();
+        // A tab always has at least one tiled pane, even if the layout doesn't spell it out
+        let split_direction = tile.children_split_direction;
+        // Even a tab with a single pane is one level deep
+        let mut max_depth = 1;
+        let tiled_pane_count =
+            collect_commands_recursive(tile, &mut tile_commands, 0, &mut max_depth);
+        if tiled_pane_count == 0 {
+            tile_commands.push((None, PaneGeometry::new(split_direction, None), false));
+        }
+        let tiled_pane_count = tiled_pane_count.max(1);
+        let floating_panes: Vec<FloatingPaneLayout> = floating_panes.collect();
+        let pane_count = tiled_pane_count + floating_panes.len();
+        let tiled_commands: Vec<MyRun> = tile_commands
+            .into_iter()
+            .map(|(run, geometry, is_split)| MyRun(run, Some(geometry), is_split))
+            .collect();
+        // Floating panes have coordinates rather than a place in a split
+        let floating_commands: Vec<MyRun> = floating_panes
+            .into_iter()
+            .map(|float| MyRun(float.run, None, false))
+            .collect();
+
+        Self {
+            index,
+            name,
+            cwd,
+            command_count: tiled_commands
+                .iter()
+                .chain(&floating_commands)
+                .filter(|command| command.is_command())
+                .count(),
+            tiled_commands,
+            floating_commands,
+            pane_count,
+            max_depth,
+            split_commands: false,
+        }
+    }
+
+    /// Adds the commands of `other` that this tab doesn't have yet
+    fn merge(&mut self, other: Tab) {
+        self.cwd = self.cwd.take().or(other.cwd);
+        for (commands, other_commands) in [
+            (&mut self.tiled_commands, other.tiled_commands),
+            (&mut self.floating_commands, other.floating_commands),
+        ] {
+            for command in other_commands {
+                let text = command.display(false, false, false);
+                if !commands
+                    .iter()
+                    .any(|existing| existing.display(false, false, false) == text)
+                {
+                    commands.push(command);
+                }
+            }
+        }
+        self.command_count = self
+            .commands()
+            .filter(|command| command.is_command())
+            .count();
+        self.pane_count = self
+            .pane_count
+            .max(other.pane_count)
+            .max(self.commands().filter(|command| !command.2).count());
+    }
+
+    /// The tiled commands followed by the floating ones
+    fn commands(&self) -> impl Iterator<Item = &MyRun> {
+        self.tiled_commands.iter().chain(&self.floating_commands)
+    }
+
+    fn display_name<'a>(&'a self, options: &'a SessionDisplayOptions) -> &'a str {
+        self.name.as_deref().unwrap_or_else(|| {
+            options
+                .unnamed_label
+                .as_deref()
+                .unwrap_or(UNNAMED_TAB_LABEL)
+        })
+    }
+}
+
+impl MyRun {
+    /// Whether the run does something in its pane, rather than only starting a shell in some
+    /// directory. Panes get the cwd of their layout or tab, so a cwd alone is still a shell.
+    fn is_command(&self) -> bool {
+        matches!(
+            self.0,
+            Some(Run::Command(_) | Run::EditFile(..) | Run::Plugin(_))
+        )
+    }
+
+    fn display(
+        &self,
+        should_color: bool,
+        full_plugin_url: bool,
+        highlight_commands: bool,
+    ) -> String {
+        match &self.0 {
+            Some(run) => display_run(run, should_color, full_plugin_url, highlight_commands),
+            None => colorize(SHELL_LABEL, "35;1", should_color),
+        }
+    }
+
+    fn strip_ansi(&mut self) {
+        let Some(run) = &mut self.0 else {
+            return;
+        };
+        match run {
+            Run::Command(run_command) => {
+                run_command.command = strip_ansi_from_path(&run_command.command);
+                for arg in &mut run_command.args {
+                    *arg = strip_ansi(arg);
+                }
+                if let Some(cwd) = &mut run_command.cwd {
+                    *cwd = strip_ansi_from_path(cwd);
+                }
+            },
+            Run::EditFile(path_buf, _, cwd) => {
+                *path_buf = strip_ansi_from_path(path_buf);
+                if let Some(cwd) = cwd {
+                    *cwd = strip_ansi_from_path(cwd);
+                }
+            },
+            Run::Cwd(path_buf) => *path_buf = strip_ansi_from_path(path_buf),
+            Run::Plugin(RunPluginOrAlias::Alias(plugin_alias)) => {
+                plugin_alias.name = strip_ansi(&plugin_alias.name);
+            },
+            // Locations are parsed as URLs, which can't hold control characters
+            Run::Plugin(RunPluginOrAlias::RunPlugin(_)) => {},
+        }
+    }
+
+    /// Drops the paths and arguments of this run, keeping only the file name of programs and
+    /// edited files
+    fn anonymize(&mut self) {
+        let file_name = |path: &Path| PathBuf::from(path.file_name().unwrap_or_default());
+        let Some(run) = &mut self.0 else {
+            return;
+        };
+        match run {
+            Run::Command(run_command) => {
+                run_command.command = file_name(&run_command.command);
+                run_command.args.clear();
+                run_command.cwd = None;
+            },
+            Run::EditFile(path_buf, _, cwd) => {
+                *path_buf = file_name(path_buf);
+                *cwd = None;
+            },
+            Run::Cwd(path_buf) => *path_buf = PathBuf::from(ANONYMIZED_PATH),
+            Run::Plugin(_) => {},
+        }
+    }
+
+    fn use_posix_paths(&mut self) {
+        let Some(run) = &mut self.0 else {
+            return;
+        };
+        match run {
+            Run::Command(run_command) => {
+                run_command.command = to_posix_path(&run_command.command);
+                if let Some(cwd) = &mut run_command.cwd {
+                    *cwd = to_posix_path(cwd);
+                }
+            },
+            Run::EditFile(path_buf, _, cwd) => {
+                *path_buf = to_posix_path(path_buf);
+                if let Some(cwd) = cwd {
+                    *cwd = to_posix_path(cwd);
+                }
+            },
+            Run::Cwd(path_buf) => *path_buf = to_posix_path(path_buf),
+            Run::Plugin(_) => {},
+        }
+    }
+
+    fn redact_home(&mut self, home: &Path) {
+        let Some(run) = &mut self.0 else {
+            return;
+        };
+        match run {
+            Run::Command(run_command) => {
+                run_command.command = redact_home(&run_command.command, home);
+                if let Some(cwd) = &mut run_command.cwd {
+                    *cwd = redact_home(cwd, home);
+                }
+            },
+            Run::EditFile(path_buf, _, cwd) => {
+                *path_buf = redact_home(path_buf, home);
+                if let Some(cwd) = cwd {
+                    *cwd = redact_home(cwd, home);
+                }
+            },
+            Run::Cwd(path_buf) => *path_buf = redact_home(path_buf, home),
+            Run::Plugin(_) => {},
+        }
+    }
+}
+
+impl Serialize for MyRun {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match &self.0 {
+            // For commands, include the CWD
+            Some(Run::Command(run_command)) => {
+                let command = format!(
+                    "{} {}",
+                    run_command.command.to_string_lossy(),
+                    run_command.args.join(" ")
+                );
+
+                let cwd = match &run_command.cwd {
+                    Some(cwd) => cwd.to_string_lossy().to_string(),
+                    None => String::new(),
+                };
+
+                let my_command = MyCommand {
+                    command,
+                    cwd,
+                    geometry: self.1,
+                };
+                my_command.serialize(serializer)
+            },
+            // Other panes only become objects when they have a geometry or are splits, to keep
+            // the output of older versions
+            _ if self.1.is_some() || self.2 => PaneRun {
+                run: self.display(false, false, false),
+                geometry: self.1,
+                split: self.2,
+            }
+            .serialize(serializer),
+            // For all other types of Run, display as normal
+            // TODO: Custom serializers for each type
+            // e.g. {
+            //  cwd: "foo/bar/baz",
+            //  type: "cwd"
+            // }
+            _ => serializer.serialize_str(&self.display(false, false, false)),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for MyRun {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum SerializedRun {
+            Command(MyCommand),
+            Pane(PaneRun),
+            Other(String),
+        }
+
+        let (other, geometry, is_split) = match SerializedRun::deserialize(deserializer)? {
+            // The arguments were joined with spaces, so arguments containing spaces get split up
+            SerializedRun::Command(MyCommand {
+                command,
+                cwd,
+                geometry,
+            }) => {
+                let (program, args) = command.split_once(' ').unwrap_or((&command, ""));
+                let run = Run::Command(RunCommand {
+                    command: PathBuf::from(program),
+                    args: args.split_whitespace().map(String::from).collect(),
+                    cwd: (!cwd.is_empty()).then(|| PathBuf::from(cwd)),
+                    ..Default::default()
+                });
+                return Ok(MyRun(Some(run), geometry, false));
+            },
+            SerializedRun::Pane(PaneRun {
+                run,
+                geometry,
+                split,
+            }) => (run, geometry, split),
+            SerializedRun::Other(other) => (other, None, false),
+        };
+        let run = if other == SHELL_LABEL {
+            return Ok(MyRun(None, geometry, false));
+        } else if let Some(cwd) = other.strip_prefix("CWD: ") {
+            Run::Cwd(PathBuf::from(cwd))
+        } else if let Some(file) = other.strip_prefix("File: ") {
+            Run::EditFile(PathBuf::from(file), None, None)
+        } else if let Some(plugin) = other.strip_prefix("Plugin: ") {
+            Run::Plugin(RunPluginOrAlias::Alias(PluginAlias {
+                name: plugin.to_owned(),
+                ..Default::default()
+            }))
+        } else {
+            return Err(serde::de::Error::custom(format!(
+                "unrecognized pane description: {other}"
+            )));
+        };
+        Ok(MyRun(Some(run), geometry, is_split))
+    }
+}
+
+/// A pane that doesn't run a command, serialized with its geometry, or the run of a split
+#[derive(Serialize, Deserialize)]
+struct PaneRun {
+    run: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    geometry: Option<PaneGeometry>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    split: bool,
+}
+
+/// With `full_plugin_url`, plugins are shown with the scheme of their location (e.g.
+/// "zellij:strider" or "file:/path/to/plugin.wasm"). With `highlight_commands`, the program of a
+/// command and its flags are colored too.
+fn display_run(
+    run: &Run,
+    should_color: bool,
+    full_plugin_url: bool,
+    highlight_commands: bool,
+) -> String {
+    let format_title = |title| colorize(title, "35;1", should_color);
+
+    match run {
+        Run::Command(run_command) if should_color && highlight_commands => {
+            format!(
+                "{} {} {}",
+                format_title("Running:"),
+                colorize(&run_command.command.to_string_lossy(), "32", true),
+                highlight_args(&run_command.args)
+            )
+        },
+        Run::Command(run_command) => {
+            format!(
+                "{} {} {}",
+                format_title("Running:"),
+                run_command.command.to_string_lossy(),
+                run_command.args.join(" ")
+            )
+        },
+        Run::EditFile(path_buf, _, _) => {
+            format!("{} {}", format_title("File:"), path_buf.to_string_lossy())
+        },
+        Run::Cwd(path_buf) => {
+            format!("{} {}", format_title("CWD:"), path_buf.to_string_lossy())
+        },
+        Run::Plugin(plugin) => format!(
+            "{} {}",
+            format_title("Plugin:"),
+            if full_plugin_url {
+                display_full_plugin_or_alias(plugin)
+            } else {
+                display_plugin_or_alias(plugin)
+            }
+        ),
+    }
+}
+
+/// Joins `args` with spaces, coloring the flags (arguments starting with `-`)
+fn highlight_args(args: &[String]) -> String {
+    args.iter()
+        .map(|arg| {
+            if arg.starts_with('-') {
+                colorize(arg, "33", true)
+            } else {
+                arg.clone()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn display_plugin_or_alias(plugin_or_alias: &RunPluginOrAlias) -> String {
+    match plugin_or_alias {
+        RunPluginOrAlias::RunPlugin(run_plugin) => run_plugin.location.to_string(),
+        RunPluginOrAlias::Alias(plugin_alias) => plugin_alias.name.to_string(),
+    }
+}
+
+fn display_full_plugin_or_alias(plugin_or_alias: &RunPluginOrAlias) -> String {
+    match plugin_or_alias {
+        RunPluginOrAlias::RunPlugin(run_plugin) => run_plugin.location.display(),
+        // An alias is only resolved to a location when the plugin is loaded
+        RunPluginOrAlias::Alias(plugin_alias) => plugin_alias.name.to_string(),
+    }
+}
+
+/// Collects the runs of all panes below `tile` into `buf`, with where they sit in their parent
+/// split, and returns the number of panes. Only leaves are counted, as nodes with children are
+/// splits rather than panes. `tile` is nested `depth` splits deep, and `max_depth` is raised to
+/// the depth of its deepest pane.
+fn collect_commands_recursive(
+    tile: TiledPaneLayout,
+    buf: &mut Vec<(Option<Run>, PaneGeometry, bool)>,
+    depth: usize,
+    max_depth: &mut usize,
+) -> usize {
+    let mut pane_count = 0;
+    if !tile.children.is_empty() {
+        *max_depth = (*max_depth).max(depth + 1);
+    }
+    for child in tile.children {
+        let is_pane = child.children.is_empty();
+        if child.run.is_some() || is_pane {
+            let geometry = PaneGeometry::new(tile.children_split_direction, child.split_size);
+            buf.push((child.run.clone(), geometry, !is_pane));
+        }
+        let nested_pane_count = collect_commands_recursive(child, buf, depth + 1, max_depth);
+        pane_count += if is_pane { 1 } else { nested_pane_count };
+    }
+    pane_count
+}
+
+/// Removes ANSI escape sequences (CSI, OSC and two-character ones) and any other control
+/// characters from `text`
+fn strip_ansi(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\u{1b}' {
+            if !c.is_control() {
+                stripped.push(c);
+            }
+            continue;
+        }
+        match chars.next() {
+            // CSI, ends with a byte in the `@` to `~` range
+            Some('[') => {
+                chars.find(|c| ('@'..='~').contains(c));
+            },
+            // OSC, ends with BEL or ST (`ESC \`)
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\u{7}' || (c == '\u{1b}' && chars.next() == Some('\\')) {
+                        break;
+                    }
+                }
+            },
+            _ => {},
+        }
+    }
+    stripped
+}
+
+fn strip_ansi_from_path(path: &Path) -> PathBuf {
+    PathBuf::from(strip_ansi(&path.to_string_lossy()))
+}
+
+/// Cuts `arg` down to `max_len` characters followed by "…" if it is any longer
+fn truncate_arg(arg: &str, max_len: usize) -> String {
+    match arg.char_indices().nth(max_len) {
+        Some((end, _)) => format!("{}…", &arg[..end]),
+        None => arg.to_owned(),
+    }
+}
+
+/// Writes `path` with forward slashes, e.g. `C:\Users\me` as `C:/Users/me`
+fn to_posix_path(path: &Path) -> PathBuf {
+    PathBuf::from(path.to_string_lossy().replace('\\', "/"))
+}
+
+fn redact_home(path: &Path, home: &Path) -> PathBuf {
+    match path.strip_prefix(home) {
+        Ok(rest) if rest.as_os_str().is_empty() => PathBuf::from("~"),
+        Ok(rest) => Path::new("~").join(rest),
+        Err(_) => path.to_path_buf(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use humantime::format_duration;
+
+    fn session_with_age(name: &str, age_secs: u64, is_active: bool) -> Session {
+        Session::new(
+            name.to_owned(),
+            Duration::from_secs(age_secs),
+            None,
+            false,
+            is_active.then_some(Duration::ZERO),
+            None,
+        )
+    }
+
+    fn session_names(sessions: &[Session]) -> Vec<&str> {
+        sessions.iter().map(|s| s.name.as_str()).collect()
+    }
+
+    fn session_from_kdl(name: &str, raw_layout: &str) -> Session {
+        let layout = Layout::from_kdl(raw_layout, None, None, None).unwrap();
+        Session::new(
+            name.to_owned(),
+            Duration::from_secs(60),
+            Some(layout),
+            false,
+            None,
+            None,
+        )
+    }
+
+    #[test]
+    fn count_commands_across_sessions() {
+        let sessions = vec![
+            session_from_kdl(
+                "one",
+                r#"layout {
+                    tab {
+                        pane command="cargo" { args "build"; }
+                        pane command="htop"
+                    }
+                }"#,
+            ),
+            session_from_kdl(
+                "two",
+                r#"layout {
+                    tab {
+                        pane command="cargo" { args "test"; }
+                        pane edit="Cargo.toml"
+                    }
+                }"#,
+            ),
+        ];
+        let counts = count_commands(&sessions);
+        assert_eq!(
+            counts,
+            vec![
+                CommandCount {
+                    command: "cargo".into(),
+                    count: 2
+                },
+                CommandCount {
+                    command: "htop".into(),
+                    count: 1
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn has_floating_without_commands() {
+        let with_floating = session_from_kdl(
+            "one",
+            r#"layout {
+                tab {
+                    pane
+                }
+                tab {
+                    pane
+                    floating_panes {
+                        pane
+                    }
+                }
+            }"#,
+        );
+        let without_floating = session_from_kdl("two", "layout { tab { pane; }; }");
+        assert!(with_floating.has_floating);
+        assert!(!without_floating.has_floating);
+    }
+
+    #[test]
+    fn has_layout_tells_missing_and_empty_layouts_apart() {
+        let empty = session_from_kdl("one", "layout {}");
+        let missing = session_with_age("two", 60, false);
+        assert!(empty.has_layout);
+        assert!(!missing.has_layout);
+        assert!(empty.tabs.is_empty() && missing.tabs.is_empty());
+    }
+
+    #[test]
+    fn tab_counts_panes_and_depth() {
+        let session = session_from_kdl(
+            "one",
+            r#"layout {
+                tab {
+                    pane split_direction="vertical" {
+                        pane command="htop"
+                        pane
+                    }
+                    pane
+                    floating_panes {
+                        pane
+                    }
+                }
+                tab {
+                }
+            }"#,
+        );
+        assert_eq!(session.tabs[0].pane_count, 4);
+        assert_eq!(session.tabs[0].command_count, 1);
+        assert_eq!(session.tabs[1].pane_count, 1);
+        assert_eq!(session.tabs[1].command_count, 0);
+        assert_eq!(session.tabs[0].max_depth, 2);
+        assert_eq!(session.tabs[1].max_depth, 1);
+    }
+
+    #[test]
+    fn sessions_of_another_session_info_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        for (name, layout) in [
+            ("alice-dev", r#"layout { tab { pane command="htop"; }; }"#),
+            ("broken", "layout {"),
+        ] {
+            std::fs::create_dir(dir.path().join(name)).unwrap();
+            std::fs::write(session_layout_file_in(dir.path(), name), layout).unwrap();
+        }
+        // Folders without a layout aren't sessions
+        std::fs::create_dir(dir.path().join("not-a-session")).unwrap();
+
+        let (sessions, warnings) = collect_sessions_with_warnings(
+            &SessionDisplayOptions::default(),
+            Some(dir.path()),
+            None,
+            &mut Timings::default(),
+        );
+        assert_eq!(session_names(&sessions), vec!["alice-dev"]);
+        assert_eq!(sessions[0].tabs[0].command_count, 1);
+        assert!(!sessions[0].is_current && sessions[0].last_active.is_none());
+        assert_eq!(
+            sessions[0].layout_path,
+            Some(dir.path().join("alice-dev").join("session-layout.kdl"))
+        );
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("skipped session broken"));
+        // Layouts are only matched when they are given
+        assert_eq!(sessions[0].template, None);
+        let htop = Layout::from_kdl(
+            r#"layout { tab { pane command="htop"; }; }"#,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let options = SessionDisplayOptions {
+            resurrect_command: true,
+            ..Default::default()
+        };
+        let (sessions, _) = collect_sessions_with_warnings(
+            &options,
+            Some(dir.path()),
+            Some(&KnownLayouts::from_layouts(&[("htop", &htop)])),
+            &mut Timings::default(),
+        );
+        assert_eq!(sessions[0].template.as_deref(), Some("htop"));
+        // The command resurrects from the layout that was read
+        let layout_file = session_layout_file_in(dir.path(), "alice-dev");
+        assert!(sessions[0]
+            .resurrect_command
+            .as_deref()
+            .unwrap()
+            .contains(&*layout_file.to_string_lossy()));
+        assert!(layout_saved_age(Some(dir.path()), "alice-dev").is_some());
+        assert!(layout_saved_age(Some(dir.path()), "not-a-session").is_none());
+    }
+
+    #[test]
+    fn command_count_leaves_out_shells_and_splits() {
+        let session = session_from_kdl(
+            "one",
+            r#"layout {
+                tab cwd="/tmp" {
+                    pane split_direction="vertical" {
+                        pane command="htop"
+                        pane split_direction="horizontal" {
+                            pane command="top"
+                            pane
+                        }
+                    }
+                }
+            }"#,
+        );
+        let tab = &session.tabs[0];
+        // Every pane gets the cwd of the tab, but the bare one still only starts a shell
+        assert_eq!(tab.pane_count, 3);
+        assert_eq!(tab.command_count, 2);
+        assert_eq!(session.command_count(), 2);
+        // The cwd of the two splits is still listed
+        assert_eq!(tab.commands().filter(|run| run.0.is_some()).count(), 5);
+    }
+
+    #[test]
+    fn sessions_editing_files() {
+        let session = session_from_kdl(
+            "one",
+            r#"layout { tab { pane edit="/etc/nginx/nginx.conf"; pane command="htop"; }; }"#,
+        );
+        assert!(session.edits_file(None));
+        assert!(session.edits_file(Some("nginx")));
+        assert!(!session.edits_file(Some("zshrc")));
+        let without_files = session_from_kdl("two", r#"layout { tab { pane command="htop"; }; }"#);
+        assert!(!without_files.edits_file(None));
+    }
+
+    #[test]
+    fn merge_duplicates_keeps_newest_and_unions_tabs() {
+        let mut old = session_from_kdl(
+            "work",
+            r#"layout {
+                tab name="editor" { pane command="nvim"; }
+                tab name="logs" { pane command="tail"; }
+            }"#,
+        );
+        old.timestamp = Duration::from_secs(600);
+        let mut new = session_from_kdl(
+            "work",
+            r#"layout { tab name="editor" { pane command="nvim"; pane command="htop"; }; }"#,
+        );
+        new.timestamp = Duration::from_secs(60);
+        new.is_active = true;
+        let other = session_with_age("other", 10, false);
+
+        let merged = merge_duplicates(vec![old, other, new]);
+        assert_eq!(session_names(&merged), vec!["work", "other"]);
+        let work = &merged[0];
+        assert_eq!(work.timestamp, Duration::from_secs(60));
+        assert!(work.is_active);
+        assert_eq!(work.merged_from, 2);
+        assert_eq!(merged[1].merged_from, 0);
+        let tabs: Vec<(Option<&str>, usize)> = work
+            .tabs
+            .iter()
+            .map(|tab| (tab.name.as_deref(), tab.command_count))
+            .collect();
+        assert_eq!(tabs, vec![(Some("editor"), 2), (Some("logs"), 1)]);
+    }
+
+    #[test]
+    fn dead_panes_are_listed_as_shells() {
+        let raw_layout = r#"layout {
+            tab {
+                pane command="htop"
+                pane
+                floating_panes {
+                    pane
+                }
+            }
+            tab {
+            }
+        }"#;
+        let displayed = |options: &SessionDisplayOptions| {
+            let mut session = session_from_kdl("one", raw_layout);
+            apply_display_options(&mut session, options);
+            session
+                .tabs
+                .iter()
+                .map(|tab| {
+                    tab.commands()
+                        .map(|command| command.display(false, false, false))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>()
+        };
+        let options = SessionDisplayOptions {
+            include_dead_panes: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            displayed(&options),
+            vec![vec!["Running: htop ", "Shell", "Shell"], vec!["Shell"]]
+        );
+        assert_eq!(
+            displayed(&SessionDisplayOptions::default()),
+            vec![vec!["Running: htop "], vec![]]
+        );
+        let shell: MyRun = serde_json::from_str(r#""Shell""#).unwrap();
+        assert!(shell.0.is_none());
+    }
+
+    #[test]
+    fn sessions_round_trip_through_json() {
+        let session = session_from_kdl(
+            "one",
+            r#"layout {
+                tab name="editor" {
+                    pane command="cargo" cwd="/tmp" {
+                        args "build" "--release"
+                    }
+                    pane edit="Cargo.toml"
+                    pane cwd="/home"
+                }
+                tab cwd="/tmp" {
+                    pane split_direction="vertical" {
+                        pane command="htop"
+                        pane split_direction="horizontal" {
+                            pane command="top"
+                            pane
+                        }
+                    }
+                }
+            }"#,
+        );
+        let json = serde_json::to_string(&vec![session.clone()]).unwrap();
+        let restored: Vec<Session> = serde_json::from_str(&json).unwrap();
+        assert_eq!(serde_json::to_string(&restored).unwrap(), json);
+        // The runs of splits stay splits, so the counts don't change when they are recomputed
+        assert_eq!(restored[0].command_count(), session.command_count());
+        let mut merged = restored[0].clone();
+        merged.merge(restored[0].clone());
+        assert_eq!(merged.command_count(), session.command_count());
+        assert_eq!(merged.tabs[1].pane_count, 3);
+    }
+
+    #[test]
+    fn tabs_serialize_merged_commands_unless_split() {
+        let mut session = session_from_kdl(
+            "one",
+            r#"layout {
+                tab name="editor" {
+                    pane command="vim"
+                    floating_panes {
+                        pane command="htop"
+                    }
+                }
+            }"#,
+        );
+        let mut options = SessionDisplayOptions {
+            merge_floating_into_tiles: true,
+            include_dead_panes: true,
+            ..Default::default()
+        };
+        apply_display_options(&mut session, &options);
+        let vim = serde_json::json!({"command": "vim ", "cwd": ""});
+        let htop = serde_json::json!({"command": "htop ", "cwd": ""});
+        assert_eq!(
+            serde_json::to_value(&session.tabs[0]).unwrap(),
+            serde_json::json!({
+                "index": 0,
+                "name": "editor",
+                "commands": [vim, htop],
+                "pane_count": 2,
+                "command_count": 2,
+                "max_depth": 1
+            })
+        );
+
+        options.merge_floating_into_tiles = false;
+        apply_display_options(&mut session, &options);
+        let tab = serde_json::to_value(&session.tabs[0]).unwrap();
+        assert_eq!(tab["tiled_commands"], serde_json::json!([vim]));
+        assert_eq!(tab["floating_commands"], serde_json::json!([htop]));
+        assert!(tab.get("commands").is_none());
+    }
+
+    #[test]
+    fn last_active_is_only_serialized_for_active_sessions() {
+        let active = serde_json::to_value(session_with_age("one", 60, true)).unwrap();
+        assert_eq!(
+            active["last_active"],
+            serde_json::json!({"secs": 0, "nanos": 0})
+        );
+        let inactive = serde_json::to_value(session_with_age("two", 60, false)).unwrap();
+        assert!(inactive.get("last_active").is_none());
+        let restored: Session = serde_json::from_value(active).unwrap();
+        assert_eq!(restored.last_active, Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn geometry_of_tiled_panes_round_trips() {
+        let session = session_from_kdl(
+            "one",
+            r#"layout {
+                tab split_direction="vertical" {
+                    pane command="vim" size="70%"
+                    pane command="htop" size=10
+                }
+            }"#,
+        );
+        let geometries: Vec<_> = session.tabs[0]
+            .tiled_commands
+            .iter()
+            .map(|command| command.1)
+            .collect();
+        assert_eq!(
+            geometries,
+            vec![
+                Some(PaneGeometry::new(
+                    SplitDirection::Vertical,
+                    Some(SplitSize::Percent(70))
+                )),
+                Some(PaneGeometry::new(
+                    SplitDirection::Vertical,
+                    Some(SplitSize::Fixed(10))
+                )),
+            ]
+        );
+        let json = serde_json::to_string(&session).unwrap();
+        let parsed: Session = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.tabs[0].tiled_commands[0].1, geometries[0]);
+    }
+
+    #[test]
+    fn filter_patterns_skip_comments_and_blank_lines() {
+        let contents = "# work sessions\nproject-\n\n  scratch  \n#tmp\n";
+        assert_eq!(parse_filter_patterns(contents), vec!["project-", "scratch"]);
+    }
+
+    #[test]
+    fn layout_saved_before_start_is_stale() {
+        assert!(is_stale(Duration::from_secs(120), Duration::from_secs(60)));
+        assert!(!is_stale(Duration::from_secs(30), Duration::from_secs(60)));
+        assert!(!is_stale(Duration::from_secs(60), Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn problems_list_missing_directories_and_commands() {
+        let session = session_from_kdl(
+            "one",
+            r#"layout {
+                tab cwd="/definitely/not/a/dir" {
+                    pane command="definitely-not-a-program"
+                    pane command="sh" cwd="/"
+                    pane command="definitely-not-a-program"
+                }
+            }"#,
+        );
+        assert_eq!(
+            session.problems(),
+            vec![
+                "missing directory: /definitely/not/a/dir",
+                "command not found: definitely-not-a-program",
+            ]
+        );
+    }
+
+    #[test]
+    fn single_tab_layout_by_name_or_index() {
+        let layout = Layout::from_kdl(
+            r#"layout {
+                tab name="editor" {
+                    pane command="vim"
+                }
+                tab name="1" {
+                    pane command="htop"
+                }
+            }"#,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let tab_name = |tab| {
+            single_tab_layout(layout.clone(), tab)
+                .map(|layout| layout.tabs.into_iter().map(|(name, _, _)| name).collect())
+        };
+        assert_eq!(tab_name("editor"), Some(vec![Some("editor".to_owned())]));
+        assert_eq!(tab_name("0"), Some(vec![Some("editor".to_owned())]));
+        // A tab name wins over an index
+        assert_eq!(tab_name("1"), Some(vec![Some("1".to_owned())]));
+        assert_eq!(tab_name("2"), None);
+        assert_eq!(tab_name("missing"), None);
+    }
+
+    #[test]
+    fn anonymized_names_follow_sorted_order() {
+        let all_names: Vec<String> = ["zeta", "alpha", "mid"].map(String::from).to_vec();
+        let mut sessions = vec![
+            session_with_age("zeta", 1, false),
+            session_with_age("mid", 2, false),
+        ];
+        anonymize_names(&mut sessions, &all_names, None);
+        assert_eq!(session_names(&sessions), vec!["session-3", "session-2"]);
+    }
+
+    #[test]
+    fn anonymized_commands_keep_only_program_names() {
+        let mut session = session_from_kdl(
+            "one",
+            r#"layout {
+                tab cwd="/home/user/secret" {
+                    pane command="/usr/bin/vim" cwd="/home/user/secret" {
+                        args "plans.txt"
+                    }
+                    pane edit="/home/user/secret/notes.md"
+                    pane cwd="/home/user/secret"
+                }
+            }"#,
+        );
+        session.anonymize_commands();
+        let tab = &session.tabs[0];
+        assert_eq!(tab.cwd, None);
+        let commands: Vec<String> = tab
+            .commands()
+            .map(|command| command.display(false, false, false))
+            .collect();
+        assert_eq!(
+            commands,
+            vec!["Running: vim ", "File: notes.md", "CWD: <redacted>"]
+        );
+    }
+
+    #[test]
+    fn programs_and_cwds_of_commands_are_redacted() {
+        let mut session = session_from_kdl(
+            "one",
+            r#"layout { tab { pane command="/home/user/bin/build" cwd="/home/user/project"; }; }"#,
+        );
+        session.redact_home(Path::new("/home/user"));
+        let Some(Run::Command(run_command)) = &session.tabs[0].tiled_commands[0].0 else {
+            panic!("expected a command");
+        };
+        assert_eq!(run_command.command, PathBuf::from("~/bin/build"));
+        assert_eq!(run_command.cwd, Some(PathBuf::from("~/project")));
+    }
+
+    #[test]
+    fn layout_path_is_redacted_and_dropped_when_anonymized() {
+        let mut session = session_with_age("secret-project", 1, false);
+        session.layout_path = Some(PathBuf::from(
+            "/home/user/.cache/zellij/secret-project/session-layout.kdl",
+        ));
+        session.redact_home(Path::new("/home/user"));
+        assert_eq!(
+            session.layout_path,
+            Some(PathBuf::from(
+                "~/.cache/zellij/secret-project/session-layout.kdl"
+            ))
+        );
+        let mut sessions = vec![session];
+        anonymize_names(&mut sessions, &["secret-project".to_owned()], None);
+        assert_eq!(sessions[0].layout_path, None);
+    }
+
+    #[test]
+    fn status_counts_by_activity() {
+        let mut sessions = vec![
+            session_with_age("one", 1, true),
+            session_with_age("two", 2, false),
+            session_with_age("three", 3, true),
+        ];
+        sessions[0].is_current = true;
+        assert_eq!(
+            StatusCounts::from_sessions(&sessions),
+            StatusCounts {
+                active: 2,
+                inactive: 1,
+                current: 1,
+            }
+        );
+    }
+
+    #[test]
+    fn highlighted_args_read_the_same_without_colors() {
+        let args = ["watch", "-x", "test", "--quiet"].map(String::from);
+        assert_eq!(strip_ansi(&highlight_args(&args)), "watch -x test --quiet");
+    }
+
+    #[test]
+    fn sort_tabs_by_count_or_name() {
+        let mut session = session_from_kdl(
+            "one",
+            r#"layout {
+                tab name="quiet" {
+                    pane command="vim"
+                }
+                tab {
+                    pane command="cargo"
+                    pane command="htop"
+                }
+                tab name="busy" {
+                    pane command="top"
+                    pane command="less"
+                    pane command="tail"
+                }
+            }"#,
+        );
+        session.sort_tabs(TabSortKey::Count);
+        let indices: Vec<usize> = session.tabs.iter().map(|tab| tab.index).collect();
+        assert_eq!(indices, vec![2, 1, 0]);
+        session.sort_tabs(TabSortKey::Name);
+        let indices: Vec<usize> = session.tabs.iter().map(|tab| tab.index).collect();
+        assert_eq!(indices, vec![2, 0, 1]);
+    }
+
+    #[test]
+    fn plugin_problems_flag_unknown_plugins() {
+        let session = session_from_kdl(
+            "one",
+            r#"layout {
+                tab {
+                    pane {
+                        plugin location="zellij:strider"
+                    }
+                    pane {
+                        plugin location="zellij:not-a-builtin"
+                    }
+                    pane {
+                        plugin location="file:/definitely/not/a/plugin.wasm"
+                    }
+                    pane {
+                        plugin location="https://example.com/plugin.wasm"
+                    }
+                    pane {
+                        plugin location="my-alias"
+                    }
+                }
+            }"#,
+        );
+        assert_eq!(
+            session.plugin_problems(&PluginAliases::default()),
+            vec![
+                "plugin not found: not-a-builtin",
+                "plugin not found: /definitely/not/a/plugin.wasm",
+                "plugin not found: my-alias",
+            ]
+        );
+    }
+
+    #[test]
+    fn strict_errors_cover_warnings_problems_and_plugins() {
+        let broken = session_from_kdl(
+            "one",
+            r#"layout {
+                tab cwd="/definitely/not/a/dir" {
+                    pane {
+                        plugin location="zellij:not-a-builtin"
+                    }
+                }
+            }"#,
+        );
+        let clean = session_from_kdl("two", "layout { tab { pane; }; }");
+        let warnings = vec!["skipped session three: corrupt".to_owned()];
+        assert_eq!(
+            strict_errors(&[broken, clean.clone()], &warnings),
+            vec![
+                "skipped session three: corrupt",
+                "session one: missing directory: /definitely/not/a/dir",
+                "session one: plugin not found: not-a-builtin",
+            ]
+        );
+        assert!(strict_errors(&[clean], &[]).is_empty());
+    }
+
+    #[test]
+    fn flat_rows_have_one_row_per_command() {
+        let session = session_from_kdl(
+            "one",
+            r#"layout {
+                tab name="editor" cwd="/src" {
+                    pane command="vim" {
+                        args "main.rs"
+                    }
+                    pane command="htop" cwd="/tmp"
+                }
+            }"#,
+        );
+        let rows = flat_rows(std::slice::from_ref(&session));
+        let summary: Vec<(&str, Option<&str>, &str, Option<&Path>)> = rows
+            .iter()
+            .map(|row| (row.session, row.tab, row.command.as_str(), row.cwd))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "one",
+                    Some("editor"),
+                    "vim main.rs",
+                    Some(Path::new("/src"))
+                ),
+                ("one", Some("editor"), "htop", Some(Path::new("/tmp"))),
+            ]
+        );
+    }
+
+    #[test]
+    fn pane_count_adds_up_tabs() {
+        let session = session_from_kdl(
+            "one",
+            r#"layout {
+                tab {
+                    pane split_direction="vertical" {
+                        pane command="vim"
+                        pane
+                    }
+                    floating_panes {
+                        pane command="htop"
+                    }
+                }
+                tab {
+                    pane
+                }
+            }"#,
+        );
+        assert_eq!(session.pane_count, 4);
+    }
+
+    #[test]
+    fn sort_commands_orders_each_tab() {
+        let mut session = session_from_kdl(
+            "one",
+            r#"layout {
+                tab {
+                    pane command="vim"
+                    pane command="cargo"
+                    pane edit="a.txt"
+                }
+                tab {
+                    pane command="top"
+                }
+            }"#,
+        );
+        session.sort_commands();
+        let commands: Vec<String> = session.tabs[0]
+            .commands()
+            .map(|command| command.display(false, false, false))
+            .collect();
+        assert_eq!(
+            commands,
+            vec!["File: a.txt", "Running: cargo ", "Running: vim "]
+        );
+        assert_eq!(session.tabs[1].commands().count(), 1);
+    }
+
+    #[test]
+    fn tab_cwd_comes_from_layout_and_tab() {
+        let session = session_from_kdl(
+            "one",
+            r#"layout {
+                cwd "/root"
+                tab cwd="project" {
+                    pane command="htop"
+                }
+                tab {
+                    pane
+                }
+            }"#,
+        );
+        assert_eq!(session.tabs[0].cwd, Some(PathBuf::from("/root/project")));
+        assert_eq!(session.tabs[1].cwd, Some(PathBuf::from("/root")));
+        assert_eq!(session.tabs[0].command_count, 1);
+    }
+
+    #[test]
+    fn panes_in_the_tab_cwd_are_only_shown_by_the_cwd_line() {
+        let session = session_from_kdl(
+            "one",
+            r#"layout {
+                tab cwd="/tmp" {
+                    pane
+                    pane cwd="/var"
+                    pane command="htop"
+                }
+            }"#,
+        );
+        let options = SessionDisplayOptions {
+            no_formatting: true,
+            merge_floating_into_tiles: true,
+            ..Default::default()
+        };
+        let mut out = Vec::new();
+        print_session(&mut out, &session, &options, &ColumnWidths::default()).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("cwd: /tmp\n"));
+        assert!(!out.contains("CWD: /tmp"));
+        assert!(out.contains("CWD: /var"));
+        assert!(out.contains("Running: htop"));
+    }
+
+    #[test]
+    fn created_within_checks_both_bounds() {
+        let day = Duration::from_secs(86_400);
+        let now = SystemTime::UNIX_EPOCH + day * 10;
+        let after = Some(SystemTime::UNIX_EPOCH + day * 5);
+        let before = Some(SystemTime::UNIX_EPOCH + day * 8);
+        // Created on day 7, 4 and 8
+        assert!(created_within(day * 3, now, after, before));
+        assert!(!created_within(day * 6, now, after, before));
+        assert!(!created_within(day * 2, now, after, before));
+        assert!(created_within(day * 2, now, after, None));
+        assert!(created_within(day * 6, now, None, before));
+    }
+
+    #[test]
+    fn key_tabs_by_name_falls_back_to_index() {
+        let mut value = serde_json::json!([{
+            "name": "one",
+            "tabs": [{ "name": "editor" }, { "name": null }, { "name": "editor" }],
+        }]);
+        key_tabs_by_name(&mut value);
+        let tabs = value[0]["tabs"].as_object().unwrap();
+        let keys: Vec<&String> = tabs.keys().collect();
+        assert_eq!(keys, vec!["1", "2", "editor"]);
+    }
+
+    #[test]
+    fn sort_sessions_by_creation() {
+        let mut sessions = vec![
+            session_with_age("middle", 20, true),
+            session_with_age("old", 30, false),
+            session_with_age("new", 10, false),
+        ];
+        let mut options = LongListOptions::default();
+        sort_sessions(&mut sessions, &options);
+        assert_eq!(session_names(&sessions), vec!["new", "middle", "old"]);
+
+        options.oldest_first = true;
+        sort_sessions(&mut sessions, &options);
+        assert_eq!(session_names(&sessions), vec!["old", "middle", "new"]);
+
+        options.sort = SessionSortKey::Active;
+        sort_sessions(&mut sessions, &options);
+        assert_eq!(session_names(&sessions), vec!["middle", "old", "new"]);
+    }
+
+    #[test]
+    fn sort_sessions_by_cwd() {
+        let mut sessions = vec![
+            session_from_kdl("none", "layout { tab; }"),
+            session_from_kdl("web", r#"layout { tab cwd="/src/web"; }"#),
+            session_from_kdl(
+                "api",
+                r#"layout { tab { pane command="make" cwd="/src/api"; }; }"#,
+            ),
+        ];
+        let mut options = LongListOptions {
+            sort: SessionSortKey::Cwd,
+            ..Default::default()
+        };
+        sort_sessions(&mut sessions, &options);
+        assert_eq!(session_names(&sessions), vec!["api", "web", "none"]);
+
+        options.missing_cwd_first = true;
+        sort_sessions(&mut sessions, &options);
+        assert_eq!(session_names(&sessions), vec!["none", "api", "web"]);
+    }
+
+    #[test]
+    fn older_than_active_keeps_inactive_sessions_before_the_oldest_active_one() {
+        let options = LongListOptions {
+            no_default_filter: true,
+            older_than_active: true,
+            ..Default::default()
+        };
+        let mut sessions = vec![
+            session_with_age("active-old", 100, true),
+            session_with_age("active-new", 50, true),
+            session_with_age("older", 200, false),
+            session_with_age("same-age", 100, false),
+            session_with_age("newer", 10, false),
+        ];
+        filter_sessions(&mut sessions, &options);
+        // A session created at the same time as the oldest active one isn't older than it
+        assert_eq!(session_names(&sessions), vec!["older"]);
+
+        let mut sessions = vec![
+            session_with_age("one", 100, false),
+            session_with_age("two", 10, false),
+        ];
+        filter_sessions(&mut sessions, &options);
+        assert_eq!(session_names(&sessions), vec!["one", "two"]);
+    }
+
+    #[test]
+    fn tab_filter_keeps_matching_tabs_and_their_sessions() {
+        let mut sessions = vec![
+            session_from_kdl(
+                "web",
+                r#"layout {
+                    tab name="web-server" { pane command="npm"; }
+                    tab name="editor" { pane command="vim"; }
+                }"#,
+            ),
+            session_from_kdl("notes", r#"layout { tab name="editor"; }"#),
+            session_from_kdl("unnamed", "layout { tab; }"),
+        ];
+        let options = LongListOptions {
+            no_default_filter: true,
+            tab_filter: Some("server".to_owned()),
+            ..Default::default()
+        };
+        filter_sessions(&mut sessions, &options);
+        assert_eq!(session_names(&sessions), vec!["web"]);
+        let tab_names: Vec<_> = sessions[0]
+            .tabs
+            .iter()
+            .map(|tab| tab.name.as_deref())
+            .collect();
+        assert_eq!(tab_names, vec![Some("web-server")]);
+    }
+
+    #[test]
+    fn only_floating_and_only_tiled_keep_one_kind_of_command() {
+        let sessions = vec![
+            session_from_kdl(
+                "both",
+                r#"layout {
+                    tab {
+                        pane command="vim"
+                        floating_panes {
+                            pane command="htop"
+                        }
+                    }
+                }"#,
+            ),
+            session_from_kdl("tiled", r#"layout { tab { pane command="make"; }; }"#),
+        ];
+        let filtered = |only_floating: bool, show_empty: bool| {
+            let mut sessions = sessions.clone();
+            let options = LongListOptions {
+                no_default_filter: true,
+                only_floating,
+                only_tiled: !only_floating,
+                show_empty,
+                ..Default::default()
+            };
+            filter_sessions(&mut sessions, &options);
+            sessions
+        };
+
+        let floating = filtered(true, false);
+        assert_eq!(session_names(&floating), vec!["both"]);
+        assert!(floating[0].tabs[0].tiled_commands.is_empty());
+        assert_eq!(floating[0].tabs[0].floating_commands.len(), 1);
+        assert_eq!(floating[0].command_count(), 1);
+        assert_eq!(session_names(&filtered(true, true)), vec!["both", "tiled"]);
+
+        let tiled = filtered(false, false);
+        assert_eq!(session_names(&tiled), vec!["both", "tiled"]);
+        assert!(tiled[0].tabs[0].floating_commands.is_empty());
+        assert_eq!(tiled[0].command_count(), 1);
+    }
+
+    #[test]
+    fn sort_sessions_breaks_ties_by_name() {
+        let mut sessions = vec![
+            session_with_age("c", 10, false),
+            session_with_age("a", 10, false),
+            session_with_age("b", 10, false),
+        ];
+        let mut options = LongListOptions::default();
+        sort_sessions(&mut sessions, &options);
+        assert_eq!(session_names(&sessions), vec!["a", "b", "c"]);
+
+        options.sort = SessionSortKey::Active;
+        sessions.reverse();
+        sort_sessions(&mut sessions, &options);
+        assert_eq!(session_names(&sessions), vec!["a", "b", "c"]);
+    }
+
+    #[test]
+    fn histogram_scales_bars_to_the_width() {
+        let counts = [("big", 10), ("small", 1), ("none", 0)];
+        assert_eq!(
+            histogram_lines(&counts, 19, false),
+            vec![
+                "big   ########## 10",
+                "small #           1",
+                "none              0",
+            ]
+        );
+    }
+
+    #[test]
+    fn probe_exit_codes() {
+        assert_eq!(probe_result(true, true), (0, "active"));
+        assert_eq!(probe_result(true, false), (0, "active"));
+        assert_eq!(probe_result(false, true), (2, "inactive"));
+        assert_eq!(probe_result(false, false), (1, "not found"));
+    }
+
+    #[test]
+    fn output_file_ending_in_json_defaults_to_json() {
+        let mut options = LongListOptions::default();
+        apply_output_file_defaults(&mut options, Path::new("out/sessions.json"));
+        assert!(options.display.json);
+        assert!(options.display.mono);
+
+        let mut options = LongListOptions {
+            oneline: true,
+            ..Default::default()
+        };
+        apply_output_file_defaults(&mut options, Path::new("sessions.json"));
+        assert!(!options.display.json);
+
+        let mut options = LongListOptions::default();
+        apply_output_file_defaults(&mut options, Path::new("sessions.txt"));
+        assert!(!options.display.json);
+    }
+
+    #[test]
+    fn truncate_arg_only_cuts_long_args() {
+        assert_eq!(truncate_arg("short", 5), "short");
+        assert_eq!(truncate_arg("longer", 5), "longe…");
+        assert_eq!(truncate_arg("äöüäöü", 3), "äöü…");
+    }
+
+    #[test]
+    fn render_session_tree_with_ascii_glyphs() {
+        let session = session_from_kdl(
+            "one",
+            r#"layout {
+                tab name="editor" {
+                    pane command="vim"
+                    pane command="htop"
+                }
+                tab name="shell" {
+                    pane cwd="/tmp"
+                }
+            }"#,
+        );
+        let options = SessionDisplayOptions {
+            no_formatting: true,
+            ..Default::default()
+        };
+        let lines = render_session_tree(&session, &options, &ASCII_TREE_GLYPHS);
+        assert_eq!(
+            lines,
+            vec![
+                "one [Created 1m ago]",
+                "+- editor",
+                "|  +- Running: vim ",
+                "|  `- Running: htop ",
+                "`- shell",
+                "   `- CWD: /tmp",
+            ]
+        );
+    }
+
+    #[test]
+    fn collapse_current_leaves_out_its_tabs() {
+        let options = SessionDisplayOptions {
+            no_formatting: true,
+            collapse_current: true,
+            show: vec![SessionField::Name, SessionField::Tabs],
+            ..Default::default()
+        };
+        let mut session = session_from_kdl("one", r#"layout { tab { pane command="htop"; }; }"#);
+        let print = |session: &Session| {
+            let mut out = Vec::new();
+            print_session(&mut out, session, &options, &ColumnWidths::default()).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        assert!(print(&session).contains("Running: htop"));
+        session.is_current = true;
+        assert_eq!(print(&session), "one\n");
+        assert_eq!(
+            render_session_tree(&session, &options, &ASCII_TREE_GLYPHS).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn floating_panes_are_only_mentioned_when_verbose() {
+        let session = session_from_kdl(
+            "one",
+            r#"layout { tab { pane; floating_panes { pane; }; }; }"#,
+        );
+        let print = |verbose: bool| {
+            let options = SessionDisplayOptions {
+                no_formatting: true,
+                verbose,
+                ..Default::default()
+            };
+            let mut out = Vec::new();
+            print_session(&mut out, &session, &options, &ColumnWidths::default()).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        assert!(!print(false).contains("Has floating panes"));
+        assert!(print(true).contains("Has floating panes"));
+    }
+
+    #[test]
+    fn strip_ansi_removes_escape_sequences() {
+        assert_eq!(strip_ansi("plain name"), "plain name");
+        assert_eq!(strip_ansi("\u{1b}[31mred\u{1b}[m"), "red");
+        assert_eq!(strip_ansi("\u{1b}]0;title\u{7}name"), "name");
+        assert_eq!(strip_ansi("\u{1b}]0;title\u{1b}\\name"), "name");
+        assert_eq!(strip_ansi("a\rb\u{8}c"), "abc");
+    }
+
+    #[test]
+    fn theme_colors_names_by_activity() {
+        let options = SessionDisplayOptions {
+            active_name_color: "92;1".to_owned(),
+            inactive_name_color: "2".to_owned(),
+            ..Default::default()
+        };
+        let theme = SessionTheme::from_options(&options);
+        assert_eq!(theme.name_color(&session_with_age("one", 10, true)), "92;1");
+        assert_eq!(theme.name_color(&session_with_age("two", 10, false)), "2");
+    }
+
+    #[test]
+    fn session_name_is_prefixed_with_id() {
+        let options = SessionDisplayOptions {
+            no_formatting: true,
+            ..Default::default()
+        };
+        let mut session = session_with_age("one", 10, false);
+        assert_eq!(format_session_name(&session, &options), "one");
+        assert_eq!(session.name_width(), 3);
+        session.id = Some(12);
+        assert_eq!(format_session_name(&session, &options), "12: one");
+        assert_eq!(session.name_width(), 7);
+    }
+
+    #[test]
+    fn plain_aligned_ages_form_columns() {
+        let options = SessionDisplayOptions {
+            no_formatting: true,
+            ..Default::default()
+        };
+        let sessions = vec![
+            session_with_age("one", 2 * 86_400 + 3 * 3_600 + 4 * 60, true),
+            session_with_age("longer", 60, false),
+        ];
+        let widths = ColumnWidths::of(&sessions, &options);
+        assert_eq!(
+            widths,
+            ColumnWidths {
+                name: 6,
+                created: 5,
+                last_active: 2,
+            }
+        );
+        let mut out = Vec::new();
+        for session in &sessions {
+            print_session(&mut out, session, &options, &widths).unwrap();
+        }
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "one    2d 3h  0s\nNo resurrection layout\nlonger    1m\nNo resurrection layout\n"
+        );
+    }
+
+    #[test]
+    fn session_markers_for_current_and_active() {
+        let options = SessionDisplayOptions {
+            current_marker: "*".to_owned(),
+            active_marker: Some("(active)".to_owned()),
+            ..Default::default()
+        };
+        let mut session = session_with_age("one", 10, true);
+        assert_eq!(session_markers(&session, &options), " (active)");
+        session.is_current = true;
+        assert_eq!(session_markers(&session, &options), " * (active)");
+        session.is_active = false;
+        assert_eq!(session_markers(&session, &options), " *");
+    }
+
+    #[test]
+    fn exec_command_quotes_the_name() {
+        assert_eq!(
+            exec_command_for("echo {name}", "my session"),
+            "echo 'my session'"
+        );
+        assert!(looks_destructive("zellij delete-session {name}"));
+        assert!(looks_destructive("rm -rf /tmp/{name}"));
+        assert!(!looks_destructive("echo {name} | tee remote.log"));
+    }
+
+    #[test]
+    fn keep_newest_keeps_order() {
+        let mut sessions = vec![
+            session_with_age("a", 30, false),
+            session_with_age("b", 10, false),
+            session_with_age("c", 20, false),
+            session_with_age("d", 20, false),
+        ];
+        keep_newest(&mut sessions, 2);
+        assert_eq!(session_names(&sessions), vec!["b", "c"]);
+        keep_newest(&mut sessions, 0);
+        assert!(sessions.is_empty());
+    }
+
+    #[test]
+    fn porcelain_v1_fields() {
+        let session = session_from_kdl(
+            "my session",
+            r#"layout {
+                tab {
+                    pane command="htop"
+                    pane edit="notes.txt"
+                }
+                tab {
+                    pane
+                }
+            }"#,
+        );
+        assert_eq!(
+            porcelain_line(&session, PorcelainVersion::V1),
+            "my session\t60\tfalse\tfalse\t2\t2"
+        );
+    }
+
+    #[test]
+    fn cwd_root_with_depth() {
+        assert_eq!(
+            cwd_root(Path::new("/home/user/project/src"), Some(3)),
+            PathBuf::from("/home/user/project")
+        );
+        assert_eq!(
+            cwd_root(Path::new("/home/user"), Some(3)),
+            PathBuf::from("/home/user")
+        );
+    }
+
+    #[test]
+    fn common_root_of_paths() {
+        assert_eq!(common_root(&[]), None);
+        assert_eq!(
+            common_root(&[
+                PathBuf::from("/home/user/project/src"),
+                PathBuf::from("/home/user/project"),
+                PathBuf::from("/home/user/project/docs"),
+            ]),
+            Some(PathBuf::from("/home/user/project"))
+        );
+        assert_eq!(
+            common_root(&[PathBuf::from("/home/a"), PathBuf::from("/tmp/b")]),
+            Some(PathBuf::from("/"))
+        );
+    }
+
+    #[test]
+    fn truncate_duration_keeps_largest_units() {
+        let age = Duration::from_secs(2 * 86_400 + 3 * 3_600 + 4 * 60 + 5);
+        assert_eq!(
+            format_duration(truncate_duration(age, 1)).to_string(),
+            "2days"
+        );
+        assert_eq!(
+            format_duration(truncate_duration(age, 2)).to_string(),
+            "2days 3h"
+        );
+        assert_eq!(truncate_duration(age, 10), age);
+        assert_eq!(
+            format_duration(truncate_duration(Duration::from_secs(2 * 86_400 + 5), 2)).to_string(),
+            "2days"
+        );
+        assert_eq!(truncate_duration(Duration::ZERO, 2), Duration::ZERO);
+    }
+
+    #[test]
+    fn shell_quote_only_when_needed() {
+        assert_eq!(shell_quote("my-session_1"), "my-session_1");
+        assert_eq!(shell_quote("my session"), "'my session'");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+        assert_eq!(shell_quote("$(rm -rf ~)"), "'$(rm -rf ~)'");
+        assert_eq!(shell_quote(""), "''");
+    }
+
+    #[test]
+    fn posix_paths_use_forward_slashes() {
+        assert_eq!(
+            to_posix_path(Path::new(r"C:\Users\me\project")),
+            PathBuf::from("C:/Users/me/project")
+        );
+        assert_eq!(
+            to_posix_path(Path::new("/already/posix")),
+            PathBuf::from("/already/posix")
+        );
+    }
+
+    #[test]
+    fn format_time_in_utc() {
+        let options = SessionDisplayOptions {
+            utc: true,
+            time_format: "%Y-%m-%d %H:%M:%S %:z".to_owned(),
+            ..Default::default()
+        };
+        let time = SystemTime::UNIX_EPOCH + Duration::from_secs(86_400 + 3_723);
+        assert_eq!(format_time(time, &options), "1970-01-02 01:02:03 +00:00");
+    }
+
+    #[test]
+    fn redact_home_replaces_prefix() {
+        let home = Path::new("/home/user");
+        assert_eq!(
+            redact_home(Path::new("/home/user/projects/zellij"), home),
+            PathBuf::from("~/projects/zellij")
+        );
+        assert_eq!(redact_home(home, home), PathBuf::from("~"));
+    }
+
+    #[test]
+    fn redact_home_keeps_paths_outside_home() {
+        let home = Path::new("/home/user");
+        assert_eq!(
+            redact_home(Path::new("/home/username/foo"), home),
+            PathBuf::from("/home/username/foo")
+        );
+        assert_eq!(redact_home(Path::new("/tmp"), home), PathBuf::from("/tmp"));
     }
 }
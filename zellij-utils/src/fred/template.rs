@@ -0,0 +1,115 @@
+//! Parsing of the `--template` format string used when listing sessions.
+//!
+//! A template is literal text with `{field}` placeholders, e.g. `"{name} ({age})"`.
+//! Literal braces are written as `{{` and `}}`.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TemplateField {
+    /// The session name
+    Name,
+    /// How long ago the session was created
+    Age,
+    /// The number of tabs in the session
+    Tabs,
+    /// The number of commands in the session
+    Commands,
+    /// The current marker ("(current)" by default) for the current session, empty otherwise
+    Current,
+}
+
+impl TemplateField {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "name" => Some(TemplateField::Name),
+            "age" => Some(TemplateField::Age),
+            "tabs" => Some(TemplateField::Tabs),
+            "commands" => Some(TemplateField::Commands),
+            "current" => Some(TemplateField::Current),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateToken {
+    Literal(String),
+    Field(TemplateField),
+}
+
+pub fn parse_template(template: &str) -> Result<Vec<TemplateToken>, String> {
+    let mut tokens = Vec::new();
+    let mut literal = String::new();
+    let mut chars = template.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                literal.push('{');
+            },
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                literal.push('}');
+            },
+            '{' => {
+                let mut name = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => name.push(c),
+                        None => return Err(format!("unclosed placeholder {{{}", name)),
+                    }
+                }
+                let field = TemplateField::from_name(&name)
+                    .ok_or_else(|| format!("unknown placeholder {{{}}}", name))?;
+                if !literal.is_empty() {
+                    tokens.push(TemplateToken::Literal(std::mem::take(&mut literal)));
+                }
+                tokens.push(TemplateToken::Field(field));
+            },
+            '}' => return Err("unmatched }, use }} for a literal brace".to_owned()),
+            c => literal.push(c),
+        }
+    }
+    if !literal.is_empty() {
+        tokens.push(TemplateToken::Literal(literal));
+    }
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_fields_and_literals() {
+        assert_eq!(
+            parse_template("{name}: {tabs} tabs{current}").unwrap(),
+            vec![
+                TemplateToken::Field(TemplateField::Name),
+                TemplateToken::Literal(": ".into()),
+                TemplateToken::Field(TemplateField::Tabs),
+                TemplateToken::Literal(" tabs".into()),
+                TemplateToken::Field(TemplateField::Current),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_escaped_braces() {
+        assert_eq!(
+            parse_template("{{{age}}}").unwrap(),
+            vec![
+                TemplateToken::Literal("{".into()),
+                TemplateToken::Field(TemplateField::Age),
+                TemplateToken::Literal("}".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(parse_template("{nope}").is_err());
+        assert!(parse_template("{name").is_err());
+        assert!(parse_template("name}").is_err());
+    }
+}
@@ -80,6 +80,86 @@ pub fn serialize_session_layout(
     Ok((document.to_string(), pane_contents))
 }
 
+/// Serializes an already parsed layout back to KDL, e.g. to export the resurrection layout of a
+/// session as a standalone layout file. Initial pane contents are not included.
+pub fn serialize_layout(layout: &Layout) -> String {
+    let mut document = KdlDocument::new();
+    let mut pane_contents = BTreeMap::new();
+    let mut layout_node = KdlNode::new("layout");
+    let mut layout_node_children = KdlDocument::new();
+    for (i, (tab_name, tiled_panes, floating_panes)) in layout.tabs.iter().enumerate() {
+        let is_focused = layout.focused_tab_index == Some(i);
+        let serialized_tab = serialize_layout_tab(
+            tab_name,
+            is_focused,
+            tiled_panes,
+            floating_panes,
+            &mut pane_contents,
+        );
+        layout_node_children.nodes_mut().push(serialized_tab);
+    }
+    serialize_new_tab_template(
+        layout.template.clone(),
+        &mut pane_contents,
+        &mut layout_node_children,
+    );
+    serialize_swap_tiled_layouts(
+        layout.swap_tiled_layouts.clone(),
+        &mut pane_contents,
+        &mut layout_node_children,
+    );
+    serialize_swap_floating_layouts(
+        layout.swap_floating_layouts.clone(),
+        &mut pane_contents,
+        &mut layout_node_children,
+    );
+    layout_node.set_children(layout_node_children);
+    document.nodes_mut().push(layout_node);
+    document.to_string()
+}
+
+fn serialize_layout_tab(
+    tab_name: &Option<String>,
+    is_focused: bool,
+    tiled_panes_layout: &TiledPaneLayout,
+    floating_panes_layout: &[FloatingPaneLayout],
+    pane_contents: &mut BTreeMap<String, String>,
+) -> KdlNode {
+    let mut serialized_tab = KdlNode::new("tab");
+    let mut serialized_tab_children = KdlDocument::new();
+    if let Some(tab_name) = tab_name {
+        serialized_tab
+            .entries_mut()
+            .push(KdlEntry::new_prop("name", tab_name.to_owned()));
+    }
+    if is_focused {
+        serialized_tab
+            .entries_mut()
+            .push(KdlEntry::new_prop("focus", KdlValue::Bool(true)));
+    }
+    if tiled_panes_layout.hide_floating_panes {
+        serialized_tab.entries_mut().push(KdlEntry::new_prop(
+            "hide_floating_panes",
+            KdlValue::Bool(true),
+        ));
+    }
+    let tiled_panes = if tiled_panes_layout.children_split_direction != SplitDirection::default()
+        || tiled_panes_layout.children_are_stacked
+    {
+        vec![tiled_panes_layout.clone()]
+    } else {
+        tiled_panes_layout.children.clone()
+    };
+    serialize_tiled_and_floating_panes(
+        &tiled_panes,
+        floating_panes_layout.to_vec(),
+        pane_contents,
+        &mut serialized_tab_children,
+    );
+    serialized_tab.set_children(serialized_tab_children);
+    serialized_tab
+}
+
 fn serialize_tab(
     tab_name: String,
     is_focused: bool,
@@ -2161,6 +2241,40 @@ mod tests {
         let kdl = serialize_session_layout(global_layout_manifest).unwrap();
         assert_snapshot!(kdl.0);
     }
+    #[test]
+    fn can_serialize_parsed_layout() {
+        // Serialized commands always start suspended, as they do in resurrection layouts
+        let kdl_layout = r#"
+            layout {
+                tab name="editor" focus=true {
+                    pane split_direction="vertical" {
+                        pane command="nvim" {
+                            args "main.rs"
+                            start_suspended true
+                        }
+                        pane size="30%"
+                    }
+                }
+                tab {
+                    pane command="cargo" cwd="/tmp" {
+                        args "watch" "-x" "test"
+                        start_suspended true
+                    }
+                    pane edit="Cargo.toml"
+                    floating_panes {
+                        pane command="htop" {
+                            start_suspended true
+                        }
+                    }
+                }
+            }
+        "#;
+        let layout = Layout::from_kdl(kdl_layout, None, None, None).unwrap();
+        let serialized = serialize_layout(&layout);
+        let reparsed = Layout::from_kdl(&serialized, None, None, None).unwrap();
+        assert_eq!(layout.tabs, reparsed.tabs);
+        assert_eq!(layout.focused_tab_index, reparsed.focused_tab_index);
+    }
 
     // utility functions
     fn parse_panegeom_from_json(data_str: &str) -> PaneGeom {
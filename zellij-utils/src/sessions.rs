@@ -1,7 +1,7 @@
 use crate::{
     consts::{
-        session_info_folder_for_session, session_layout_cache_file_name,
-        ZELLIJ_SESSION_INFO_CACHE_DIR, ZELLIJ_SOCK_DIR,
+        session_info_folder_for_session, session_layout_cache_file_name, session_layout_file_in,
+        ZELLIJ_SESSION_INFO_CACHE_DIR, ZELLIJ_SOCK_DIR, ZELLIJ_SOCK_MAX_LENGTH,
     },
     envs,
     input::layout::Layout,
@@ -12,6 +12,7 @@ use humantime::format_duration;
 use interprocess::local_socket::LocalSocketStream;
 use std::collections::HashMap;
 use std::os::unix::fs::FileTypeExt;
+use std::path::Path;
 use std::time::{Duration, SystemTime};
 use std::{fs, io, process};
 use suggest::Suggest;
@@ -42,15 +43,24 @@ pub fn get_sessions() -> Result<Vec<(String, Duration)>, io::ErrorKind> {
 }
 
 pub fn get_resurrectable_sessions() -> Vec<(String, Duration)> {
-    match fs::read_dir(&*ZELLIJ_SESSION_INFO_CACHE_DIR) {
+    get_resurrectable_sessions_in(&ZELLIJ_SESSION_INFO_CACHE_DIR)
+}
+
+/// The resurrectable sessions of a session_info folder, e.g. the one of another user. Sessions
+/// whose layout can't be checked for lack of permissions are kept, so that reading their layout
+/// reports the error.
+pub fn get_resurrectable_sessions_in(session_info_dir: &Path) -> Vec<(String, Duration)> {
+    match fs::read_dir(session_info_dir) {
         Ok(files_in_session_info_folder) => {
             let files_that_are_folders = files_in_session_info_folder
                 .filter_map(|f| f.ok().map(|f| f.path()))
                 .filter(|f| f.is_dir());
             files_that_are_folders
                 .filter_map(|folder_name| {
-                    let layout_file_name =
-                        session_layout_cache_file_name(&folder_name.display().to_string());
+                    let session_name = folder_name
+                        .file_name()
+                        .map(|f| std::path::PathBuf::from(f).display().to_string())?;
+                    let layout_file_name = session_layout_file_in(session_info_dir, &session_name);
                     let ctime = match std::fs::metadata(&layout_file_name)
                         .and_then(|metadata| metadata.created())
                     {
@@ -62,13 +72,9 @@ pub fn get_resurrectable_sessions() -> Vec<(String, Duration)> {
                             Duration::from_secs(ctime.elapsed().ok().unwrap_or_default().as_secs())
                         })
                         .unwrap_or_default();
-                    let session_name = folder_name
-                        .file_name()
-                        .map(|f| std::path::PathBuf::from(f).display().to_string())?;
-                    if std::path::Path::new(&layout_file_name).exists() {
-                        Some((session_name, elapsed_duration))
-                    } else {
-                        None
+                    match layout_file_name.try_exists() {
+                        Ok(false) => None,
+                        Ok(true) | Err(_) => Some((session_name, elapsed_duration)),
                     }
                 })
                 .collect()
@@ -76,7 +82,7 @@ pub fn get_resurrectable_sessions() -> Vec<(String, Duration)> {
         Err(e) => {
             log::error!(
                 "Failed to read session_info cache folder: \"{:?}\": {:?}",
-                &*ZELLIJ_SESSION_INFO_CACHE_DIR,
+                session_info_dir,
                 e
             );
             vec![]
@@ -361,8 +367,38 @@ pub fn resurrection_layout(session_name_to_resurrect: &str) -> Result<Option<Lay
             return Ok(None);
         },
     };
+    parse_resurrection_layout(&raw_layout, &layout_file_name)
+}
+
+/// Like `resurrection_layout`, for a session of another session_info folder. A layout that
+/// exists but can't be read (e.g. for lack of permissions) is an error rather than no layout.
+pub fn resurrection_layout_in(
+    session_info_dir: &Path,
+    session_name: &str,
+) -> Result<Option<Layout>, String> {
+    let layout_file_name = session_layout_file_in(session_info_dir, session_name);
+    let raw_layout = match std::fs::read_to_string(&layout_file_name) {
+        Ok(raw_layout) => raw_layout,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            return Ok(None);
+        },
+        Err(e) => {
+            return Err(format!(
+                "Failed to read resurrection layout file {}: {}.",
+                layout_file_name.display(),
+                e
+            ));
+        },
+    };
+    parse_resurrection_layout(&raw_layout, &layout_file_name)
+}
+
+fn parse_resurrection_layout(
+    raw_layout: &str,
+    layout_file_name: &Path,
+) -> Result<Option<Layout>, String> {
     match Layout::from_kdl(
-        &raw_layout,
+        raw_layout,
         Some(layout_file_name.display().to_string()),
         None,
         None,
@@ -430,17 +466,51 @@ pub fn assert_dead_session(name: &str, force: bool) {
     process::exit(1);
 }
 
-pub fn assert_session_ne(name: &str) {
+/// Checks that a session can be created with `name` as it is. The name is also the file name of
+/// the session's socket and resurrection layout, whose path has a length limit.
+pub fn validate_session_name(name: &str) -> Result<(), String> {
+    check_session_name(name)?;
+    // The socket path is the socket directory, a separator and the name
+    let available_length = ZELLIJ_SOCK_MAX_LENGTH
+        .saturating_sub(ZELLIJ_SOCK_DIR.as_os_str().len())
+        .saturating_sub(2);
+    if name.len() > available_length {
+        return Err(format!(
+            "Session name must be at most {} characters long.",
+            available_length
+        ));
+    }
+    Ok(())
+}
+
+/// For tools that create sessions from names they read elsewhere: returns `name` without any
+/// trailing line breaks (as left by shell pipelines) if a session can be created with it.
+/// Everything else is kept as is.
+pub fn normalize_session_name(name: &str) -> Result<String, String> {
+    let name = name.trim_end_matches(['\n', '\r']);
+    validate_session_name(name)?;
+    Ok(name.to_owned())
+}
+
+/// The rules of `validate_session_name` that don't depend on where the socket lives
+fn check_session_name(name: &str) -> Result<(), String> {
     if name.trim().is_empty() {
-        eprintln!("Session name cannot be empty. Please provide a specific session name.");
-        process::exit(1);
+        return Err(
+            "Session name cannot be empty. Please provide a specific session name.".to_owned(),
+        );
     }
     if name == "." || name == ".." {
-        eprintln!("Invalid session name: \"{}\".", name);
-        process::exit(1);
+        return Err(format!("Invalid session name: \"{}\".", name));
     }
     if name.contains('/') {
-        eprintln!("Session name cannot contain '/'.");
+        return Err("Session name cannot contain '/'.".to_owned());
+    }
+    Ok(())
+}
+
+pub fn assert_session_ne(name: &str) {
+    if let Err(e) = check_session_name(name) {
+        eprintln!("{}", e);
         process::exit(1);
     }
 
@@ -495,6 +565,15 @@ pub fn get_name_generator() -> impl Iterator<Item = String> {
     names::Generator::new(&ADJECTIVES, &NOUNS, names::Name::Plain)
 }
 
+/// Whether `name` looks like it was made by the generator from `get_name_generator`, as opposed
+/// to having been chosen by the user
+pub fn is_generated_session_name(name: &str) -> bool {
+    match name.split_once('-') {
+        Some((adjective, noun)) => ADJECTIVES.contains(&adjective) && NOUNS.contains(&noun),
+        None => false,
+    }
+}
+
 const ADJECTIVES: &[&'static str] = &[
     "adamant",
     "adept",
@@ -630,3 +709,48 @@ const NOUNS: &[&'static str] = &[
     "yak",
     "zebra",
 ];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_names_are_validated_and_normalized() {
+        assert_eq!(validate_session_name("my-project"), Ok(()));
+        // Validation takes the name as it is, only normalizing drops trailing line breaks
+        assert_eq!(validate_session_name(" spaced "), Ok(()));
+        assert_eq!(normalize_session_name("piped\r\n"), Ok("piped".to_owned()));
+        assert_eq!(
+            normalize_session_name(" spaced "),
+            Ok(" spaced ".to_owned())
+        );
+        assert!(validate_session_name("").is_err());
+        assert!(normalize_session_name("  \n").is_err());
+        assert!(validate_session_name("..").is_err());
+        assert!(normalize_session_name("a/b").is_err());
+    }
+
+    #[test]
+    fn session_names_must_fit_in_the_socket_path() {
+        let available_length = ZELLIJ_SOCK_MAX_LENGTH - ZELLIJ_SOCK_DIR.as_os_str().len() - 2;
+        let longest = "a".repeat(available_length);
+        assert_eq!(validate_session_name(&longest), Ok(()));
+        let error = normalize_session_name(&format!("{}a\n", longest)).unwrap_err();
+        assert!(error.contains(&available_length.to_string()));
+    }
+
+    #[test]
+    fn generated_session_names_are_told_apart() {
+        assert!(is_generated_session_name("wise-aardvark"));
+        assert!(get_name_generator()
+            .take(20)
+            .all(|name| is_generated_session_name(&name)));
+        assert!(!is_generated_session_name("my-project"));
+        assert!(!is_generated_session_name("dotfiles"));
+        // Near misses: reordered, suffixed or differently cased generated names
+        assert!(!is_generated_session_name("aardvark-wise"));
+        assert!(!is_generated_session_name("wise-aardvark-2"));
+        assert!(!is_generated_session_name("Wise-Aardvark"));
+        assert!(!is_generated_session_name("wise-"));
+    }
+}